@@ -0,0 +1,259 @@
+// Importa los tipos y funciones de nalgebra_glm necesarios para construir matrices de vista y proyección.
+use nalgebra_glm::{look_at, ortho, perspective, Mat4, Vec3};
+
+// Límite de la elevación para evitar que la cámara se voltee al pasar por los polos.
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+// Límites de la distancia al objetivo, para no atravesar el planeta ni salir del plano lejano.
+const MIN_DISTANCE: f32 = 1.5;
+const MAX_DISTANCE: f32 = 50.0;
+
+// Factor por el que se multiplica la distancia en cada paso de la rueda del ratón.
+const ZOOM_STEP: f32 = 0.9;
+
+// Límites del campo de visión vertical de la proyección en perspectiva.
+const MIN_FOV: f32 = 20.0 * std::f32::consts::PI / 180.0;
+const MAX_FOV: f32 = 120.0 * std::f32::consts::PI / 180.0;
+
+// Límites de la velocidad de la cámara libre, en unidades por segundo.
+const MIN_MOVE_SPEED: f32 = 0.1;
+const MAX_MOVE_SPEED: f32 = 20.0;
+
+// Define cómo se mueve la cámara.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    Orbit, // Gira alrededor de `target` a `distance`.
+    Fly,   // Se mueve libremente desde `position`, mirando según `yaw` y `pitch`.
+}
+
+impl CameraMode {
+    // Devuelve un nombre legible para mostrar en pantalla.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "Orbital",
+            CameraMode::Fly => "Libre",
+        }
+    }
+}
+
+// Define una cámara que puede girar alrededor de un punto objetivo usando coordenadas esféricas o volar libremente.
+// Ambos modos comparten `yaw` y `pitch`: la mirada siempre apunta en la dirección opuesta al desplazamiento
+// orbital, así que al cambiar de modo la vista no salta.
+pub struct Camera {
+    pub mode: CameraMode, // Modo de movimiento actual.
+    pub yaw: f32,         // Azimut en radianes, medido alrededor del eje Y.
+    pub pitch: f32,       // Elevación en radianes, limitada a (-89°, 89°).
+    pub distance: f32,    // Distancia entre la cámara y el objetivo (modo orbital).
+    pub target: Vec3,     // Punto hacia el que mira la cámara (modo orbital).
+    pub position: Vec3,   // Posición de la cámara (modo libre).
+    pub move_speed: f32,  // Velocidad de la cámara libre en unidades por segundo.
+    pub fov_y: f32,       // Campo de visión vertical en radianes; lo usa la proyección en perspectiva.
+}
+
+impl Camera {
+    // Crea una cámara orbital sobre el eje Z positivo mirando al origen desde la distancia dada, con un campo de
+    // visión vertical de `fov_y` radianes.
+    pub fn new(distance: f32, fov_y: f32) -> Self {
+        Camera {
+            mode: CameraMode::Orbit,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance,
+            target: Vec3::new(0.0, 0.0, 0.0),
+            position: Vec3::new(0.0, 0.0, distance),
+            move_speed: 2.0,
+            fov_y: fov_y.clamp(MIN_FOV, MAX_FOV),
+        }
+    }
+
+    // Cambia el campo de visión en `delta` radianes, dentro de 20°–120°.
+    pub fn adjust_fov(&mut self, delta: f32) {
+        self.fov_y = (self.fov_y + delta).clamp(MIN_FOV, MAX_FOV);
+    }
+
+    // Gira la cámara alrededor del objetivo (modo orbital) o gira la mirada en su lugar (modo libre),
+    // limitando la elevación.
+    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        self.yaw += delta_yaw;
+        self.pitch = (self.pitch + delta_pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // Aleja la cámara orbital hasta al menos `distance`, sin acercarla, para que una escena grande quede a la vista
+    // sin perder el acercamiento elegido en las pequeñas.
+    pub fn ensure_distance(&mut self, distance: f32) {
+        self.distance = self.distance.max(distance.min(MAX_DISTANCE));
+    }
+
+    // Acerca (valores positivos) o aleja (negativos) la cámara de forma multiplicativa.
+    // En modo libre la rueda cambia la velocidad de movimiento en vez de la distancia.
+    pub fn zoom(&mut self, notches: f32) {
+        match self.mode {
+            CameraMode::Orbit => {
+                self.distance = (self.distance * ZOOM_STEP.powf(notches)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+            }
+            CameraMode::Fly => {
+                self.move_speed = (self.move_speed / ZOOM_STEP.powf(notches)).clamp(MIN_MOVE_SPEED, MAX_MOVE_SPEED);
+            }
+        }
+    }
+
+    // Cambia entre el modo orbital y el libre conservando la vista actual.
+    pub fn toggle_mode(&mut self) {
+        match self.mode {
+            CameraMode::Orbit => {
+                self.position = self.eye();
+                self.mode = CameraMode::Fly;
+            }
+            CameraMode::Fly => {
+                self.target = self.position + self.forward() * self.distance;
+                self.mode = CameraMode::Orbit;
+            }
+        }
+    }
+
+    // Mueve la cámara libre. `direction` está en ejes de la cámara: X a la derecha, Y hacia arriba (en el mundo)
+    // y Z hacia adelante. Se normaliza para que moverse en diagonal no sea más rápido. No hace nada en modo orbital.
+    pub fn fly(&mut self, direction: Vec3, delta_time: f32) {
+        if self.mode != CameraMode::Fly || direction.magnitude_squared() == 0.0 {
+            return;
+        }
+
+        let forward = self.forward();
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let right = forward.cross(&up).normalize();
+        let world = (right * direction.x + up * direction.y + forward * direction.z).normalize();
+        self.position += world * self.move_speed * delta_time;
+    }
+
+    // Devuelve la dirección unitaria hacia la que mira la cámara.
+    pub fn forward(&self) -> Vec3 {
+        -self.orbit_offset()
+    }
+
+    // Calcula la posición de la cámara en el mundo.
+    pub fn eye(&self) -> Vec3 {
+        match self.mode {
+            CameraMode::Orbit => self.target + self.orbit_offset() * self.distance,
+            CameraMode::Fly => self.position,
+        }
+    }
+
+    // Construye la matriz de vista. Como la elevación nunca llega a ±90°, el vector "arriba" puede ser siempre +Y.
+    pub fn view_matrix(&self) -> Mat4 {
+        let eye = self.eye();
+        look_at(&eye, &(eye + self.forward()), &Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    // Vector unitario del objetivo hacia la cámara según sus coordenadas esféricas.
+    fn orbit_offset(&self) -> Vec3 {
+        Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        )
+    }
+}
+
+// Define el tipo de proyección usado para pasar del espacio de la cámara al espacio de recorte. El campo de visión
+// de la perspectiva es de la cámara, así que se conserva al pasar por la proyección ortográfica.
+#[derive(Debug, Clone, Copy)]
+pub enum Projection {
+    // Proyección en perspectiva, con escorzo según la distancia.
+    Perspective {
+        near: f32, // Distancia al plano cercano.
+        far: f32,  // Distancia al plano lejano.
+    },
+    // Proyección ortográfica, sin escorzo. La coordenada `w` siempre vale 1.
+    Orthographic {
+        half_height: f32, // Mitad de la altura visible en unidades del mundo.
+        near: f32,        // Distancia al plano cercano.
+        far: f32,         // Distancia al plano lejano.
+    },
+}
+
+impl Projection {
+    // Calcula la matriz de proyección para la relación de aspecto dada (ancho / alto) y el campo de visión vertical
+    // `fov_y` de la cámara, que la proyección ortográfica no usa.
+    pub fn matrix(&self, aspect: f32, fov_y: f32) -> Mat4 {
+        match *self {
+            Projection::Perspective { near, far } => perspective(aspect, fov_y, near, far),
+            Projection::Orthographic { half_height, near, far } => {
+                let half_width = half_height * aspect;
+                ortho(-half_width, half_width, -half_height, half_height, near, far)
+            }
+        }
+    }
+
+    // Devuelve la otra proyección, conservando los planos cercano y lejano. El tamaño ortográfico se elige para
+    // que un objeto a `distance` se vea del mismo tamaño que con el campo de visión `fov_y`; al volver a la
+    // perspectiva se usa de nuevo el campo de visión de la cámara.
+    pub fn toggled(&self, distance: f32, fov_y: f32) -> Self {
+        match *self {
+            Projection::Perspective { near, far } => Projection::Orthographic {
+                half_height: distance * (fov_y * 0.5).tan(),
+                near,
+                far,
+            },
+            Projection::Orthographic { near, far, .. } => Projection::Perspective { near, far },
+        }
+    }
+
+    // Indica si la proyección es en perspectiva, la única que usa el campo de visión.
+    pub fn is_perspective(&self) -> bool {
+        matches!(self, Projection::Perspective { .. })
+    }
+
+    // Devuelve las distancias a los planos cercano y lejano.
+    pub fn depth_range(&self) -> (f32, f32) {
+        match *self {
+            Projection::Perspective { near, far } | Projection::Orthographic { near, far, .. } => (near, far),
+        }
+    }
+
+    // Devuelve un nombre legible para mostrar en pantalla.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective { .. } => "Perspectiva",
+            Projection::Orthographic { .. } => "Ortográfica",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn toggling_to_orthographic_and_back_keeps_the_fov() {
+        let fov_y = 60f32.to_radians();
+        let mut camera = Camera::new(5.0, fov_y);
+        let perspective = Projection::Perspective { near: 0.1, far: 100.0 };
+        let before = perspective.matrix(1.5, camera.fov_y);
+
+        // A la distancia de la cámara la vista ortográfica cubre la misma altura que la perspectiva.
+        let orthographic = perspective.toggled(camera.distance, camera.fov_y);
+        let Projection::Orthographic { half_height, .. } = orthographic else {
+            panic!("expected an orthographic projection");
+        };
+        assert!((half_height - 5.0 * (fov_y * 0.5).tan()).abs() < 1e-6);
+
+        // Acercarse en la vista ortográfica ya no cambia el campo de visión al volver a la perspectiva.
+        camera.zoom(3.0);
+        let back = orthographic.toggled(camera.distance, camera.fov_y);
+        assert!(back.is_perspective());
+        assert_eq!(camera.fov_y, fov_y);
+        assert_eq!(back.matrix(1.5, camera.fov_y), before);
+    }
+
+    #[test]
+    fn adjust_fov_stays_within_the_limits() {
+        let mut camera = Camera::new(5.0, 60f32.to_radians());
+        camera.adjust_fov(5f32.to_radians());
+        assert!((camera.fov_y.to_degrees() - 65.0).abs() < 1e-4);
+        camera.adjust_fov(PI);
+        assert_eq!(camera.fov_y, MAX_FOV);
+        camera.adjust_fov(-PI);
+        assert_eq!(camera.fov_y, MIN_FOV);
+    }
+}
@@ -0,0 +1,102 @@
+// Define el reloj de la simulación: sigue al tiempo real multiplicado por una velocidad, y no avanza mientras
+// está en pausa. Acumula el tiempo de la simulación cuadro a cuadro, así que al cambiar la velocidad o al
+// reanudar continúa exactamente desde donde estaba, sin saltos.
+#[derive(Debug, Clone, Copy)]
+pub struct SimClock {
+    elapsed: f32,    // Último tiempo real recibido en `tick`, en segundos.
+    sim_time: f32,   // Tiempo de la simulación acumulado, en segundos.
+    time_scale: f32, // Segundos de simulación por segundo real.
+    paused: bool,    // Si es verdadero, el tiempo de la simulación no avanza.
+}
+
+impl SimClock {
+    // Rango permitido para la velocidad de la simulación. El mínimo es mayor que cero porque detener el tiempo
+    // es lo que hace la pausa, que además conserva la velocidad elegida.
+    pub const MIN_TIME_SCALE: f32 = 0.125;
+    pub const MAX_TIME_SCALE: f32 = 16.0;
+
+    // Crea un reloj en cero, sin pausa y a velocidad normal.
+    pub fn new() -> Self {
+        SimClock {
+            elapsed: 0.0,
+            sim_time: 0.0,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+
+    // Avanza el reloj hasta el tiempo real `now`. Debe llamarse una vez por cuadro, antes de leer `sim_time`.
+    pub fn tick(&mut self, now: f32) {
+        if !self.paused {
+            self.sim_time += (now - self.elapsed) * self.time_scale;
+        }
+        self.elapsed = now;
+    }
+
+    // Devuelve el tiempo de la simulación en segundos.
+    pub fn sim_time(&self) -> f32 {
+        self.sim_time
+    }
+
+    // Pausa o reanuda la simulación.
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    // Indica si la simulación está en pausa.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Multiplica la velocidad de la simulación por `factor`, dentro de [MIN_TIME_SCALE, MAX_TIME_SCALE].
+    pub fn scale_speed(&mut self, factor: f32) {
+        self.time_scale = (self.time_scale * factor).clamp(Self::MIN_TIME_SCALE, Self::MAX_TIME_SCALE);
+    }
+
+    // Devuelve la velocidad actual de la simulación.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        SimClock::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_and_resume_continue_without_a_jump() {
+        let mut clock = SimClock::new();
+        for now in [0.5, 1.0, 1.5] {
+            clock.tick(now);
+        }
+        assert_eq!(clock.sim_time(), 1.5);
+
+        // En pausa el tiempo real sigue corriendo, pero el de la simulación no.
+        clock.toggle_pause();
+        for now in [2.0, 3.0, 4.5] {
+            clock.tick(now);
+            assert_eq!(clock.sim_time(), 1.5);
+        }
+
+        // Al reanudar solo cuenta lo que pasa desde el último cuadro, no los 3 segundos en pausa.
+        clock.toggle_pause();
+        clock.tick(5.0);
+        assert_eq!(clock.sim_time(), 2.0);
+
+        // Con el doble de velocidad, cada segundo real suma dos.
+        clock.scale_speed(2.0);
+        clock.tick(6.0);
+        assert_eq!(clock.sim_time(), 4.0);
+        clock.toggle_pause();
+        clock.tick(8.0);
+        clock.toggle_pause();
+        clock.tick(8.5);
+        assert_eq!(clock.sim_time(), 5.0);
+    }
+}
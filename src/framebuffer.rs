@@ -1,8 +1,13 @@
 // Utiliza el tipo Vec3 de la biblioteca nalgebra_glm para manejar vectores de 3D.
+use crate::font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH}; // Para escribir texto sin raylib.
 use nalgebra_glm::Vec3;
+use std::ops::Add;
+
+// Cantidad de escrituras de un píxel que se muestra en rojo en el mapa de sobredibujo; las mayores se saturan.
+const OVERDRAW_MAX: u16 = 8;
 
 // Define una estructura para representar un color con componentes rojo, verde y azul (RGB).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     pub r: u8, // Componente rojo del color.
     pub g: u8, // Componente verde del color.
@@ -32,7 +37,7 @@ impl Color {
 
     // Convierte un color a un vector de 3D (Vec3). Los componentes del color se normalizan de 0-255 a 0.0-1.0.
     #[inline]
-    pub fn to_vec3(&self) -> Vec3 {
+    pub fn to_vec3(self) -> Vec3 {
         Vec3::new(
             self.r as f32 / 255.0,
             self.g as f32 / 255.0,
@@ -42,17 +47,163 @@ impl Color {
 
     // Convierte el color a un tipo de color compatible con la biblioteca Raylib.
     #[inline]
-    pub fn to_raylib(&self) -> raylib::color::Color {
+    pub fn to_raylib(self) -> raylib::color::Color {
         raylib::color::Color::new(self.r, self.g, self.b, 255)
     }
+
+    // Interpola entre este color (`t` = 0.0) y `other` (`t` = 1.0), canal por canal y redondeando.
+    // `t` se limita a [0, 1], así que el resultado nunca sale del rango entre ambos colores.
+    #[inline]
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    // Lee un color escrito como `#RGB`, `#RRGGBB` o `#RRGGBBAA` en hexadecimal. El alfa se acepta para que
+    // sirvan colores copiados de otras herramientas, pero se descarta porque `Color` no lo guarda.
+    pub fn from_hex(s: &str) -> Result<Color, String> {
+        let invalid = || format!("invalid color '{}': expected #RGB, #RRGGBB or #RRGGBBAA", s);
+        let digits = s.strip_prefix('#').ok_or_else(invalid)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let channel = |i: usize, len: usize| {
+            u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).map_err(|_| invalid())
+        };
+        match digits.len() {
+            // Cada dígito se repite: `#F80` es `#FF8800`.
+            3 => Ok(Color::new(channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17)),
+            6 | 8 => Ok(Color::new(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+            _ => Err(invalid()),
+        }
+    }
+
+    // Escribe el color como `#RRGGBB`, el formato que acepta `from_hex`. Todavía no hay un archivo de escenas
+    // que se guarde desde el programa, así que por ahora solo lo usan las pruebas.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn to_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+
+    // Multiplica cada canal por `factor`, saturando en 0 y 255.
+    #[inline]
+    pub fn scale(self, factor: f32) -> Color {
+        let scale = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+        Color::new(scale(self.r), scale(self.g), scale(self.b))
+    }
+}
+
+// Suma dos colores canal por canal, saturando en 255 (como al acumular luz).
+impl Add for Color {
+    type Output = Color;
+
+    #[inline]
+    fn add(self, other: Color) -> Color {
+        Color::new(
+            self.r.saturating_add(other.r),
+            self.g.saturating_add(other.g),
+            self.b.saturating_add(other.b),
+        )
+    }
+}
+
+// Aplica la curva de codificación sRGB (por tramos) a un valor lineal entre 0.0 y 1.0.
+#[inline]
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Parámetros del efecto de bloom (resplandor alrededor de las zonas brillantes).
+#[derive(Debug, Clone, Copy)]
+pub struct BloomSettings {
+    pub threshold: f32,     // Luminancia (0.0-1.0) a partir de la cual un píxel empieza a brillar.
+    pub blur_radius: usize, // Radio en píxeles del desenfoque gaussiano.
+    pub intensity: f32,     // Cuánto del resplandor desenfocado se suma a la imagen.
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        BloomSettings {
+            threshold: 0.8,
+            blur_radius: 8,
+            intensity: 0.6,
+        }
+    }
+}
+
+// Define la función de comparación que usa la prueba de profundidad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthFunc {
+    Less,      // Pasa si el fragmento está más cerca que el valor almacenado.
+    LessEqual, // Pasa si está más cerca o a la misma profundidad.
+    Greater,   // Pasa si está más lejos (útil para depurar el orden de dibujo).
+    Always,    // Siempre pasa; desactiva la prueba de profundidad.
+}
+
+impl DepthFunc {
+    // Compara la profundidad de un fragmento con la almacenada en el búfer.
+    #[inline]
+    pub fn test(self, depth: f32, stored: f32) -> bool {
+        match self {
+            DepthFunc::Less => depth < stored,
+            DepthFunc::LessEqual => depth <= stored,
+            DepthFunc::Greater => depth > stored || stored == f32::INFINITY,
+            DepthFunc::Always => true,
+        }
+    }
+
+    // Devuelve la siguiente función, para poder recorrerlas con una sola tecla.
+    pub fn next(self) -> Self {
+        match self {
+            DepthFunc::Less => DepthFunc::LessEqual,
+            DepthFunc::LessEqual => DepthFunc::Greater,
+            DepthFunc::Greater => DepthFunc::Always,
+            DepthFunc::Always => DepthFunc::Less,
+        }
+    }
+}
+
+// Define cómo se combina el color de un fragmento (`src`) con el que ya tiene el píxel (`dst`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Replace,  // Reemplaza el color; ignora el alfa.
+    Alpha,    // Mezcla según el alfa: `src * alpha + dst * (1 - alpha)`.
+    Add,      // Suma `src * alpha`, saturando en blanco; para acumular resplandores.
+    Multiply, // Multiplica por `src` con peso `alpha`; para oscurecer con sombras u oclusión.
+}
+
+impl BlendMode {
+    // Combina `src`, con opacidad `alpha` (0.0-1.0), sobre `dst`.
+    #[inline]
+    pub fn apply(self, dst: Color, src: Color, alpha: f32) -> Color {
+        let alpha = alpha.clamp(0.0, 1.0);
+        match self {
+            BlendMode::Replace => src,
+            BlendMode::Alpha => dst.lerp(src, alpha),
+            BlendMode::Add => dst + src.scale(alpha),
+            BlendMode::Multiply => {
+                let product = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+                let multiplied = Color::new(product(dst.r, src.r), product(dst.g, src.g), product(dst.b, src.b));
+                dst.lerp(multiplied, alpha)
+            }
+        }
+    }
 }
 
-// Define el búfer de fotogramas, que almacena los datos de píxeles y profundidad de una imagen renderizada.
+// Define el búfer de fotogramas, que almacena los datos de píxeles de una imagen renderizada. La profundidad
+// la guarda el `Renderer`, que es el único que la prueba.
 pub struct Framebuffer {
     pub width: usize, // Ancho del búfer de fotogramas en píxeles.
     pub height: usize, // Alto del búfer de fotogramas en píxeles.
     pub buffer: Vec<u8>, // Búfer de píxeles en formato RGBA (4 bytes por píxel).
-    pub zbuffer: Vec<f32>, // Búfer de profundidad para el Z-buffering.
+    pub overdraw: Vec<u16>, // Veces que se escribió cada píxel desde la última limpieza.
 }
 
 impl Framebuffer {
@@ -62,10 +213,21 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height * 4], // Inicializa el búfer de color a negro.
-            zbuffer: vec![f32::INFINITY; width * height], // Inicializa el búfer de profundidad a infinito.
+            overdraw: vec![0; width * height],
         }
     }
 
+    // Cambia el tamaño del búfer. El contenido no se conserva: queda en negro. La profundidad del `Renderer` se
+    // redimensiona con `Renderer::resize`.
+    // `Vec::resize` reutiliza la memoria ya reservada cuando el nuevo tamaño cabe en ella.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.buffer.resize(width * height * 4, 0);
+        self.overdraw.resize(width * height, 0);
+        self.clear(Color::BLACK);
+    }
+
     // Limpia el búfer de fotogramas, estableciendo todos los píxeles a un color específico.
     #[inline]
     pub fn clear(&mut self, color: Color) {
@@ -76,31 +238,886 @@ impl Framebuffer {
             self.buffer[idx + 2] = color.b;
             self.buffer[idx + 3] = 255; // El canal alfa se establece en 255 (opaco).
         }
-        self.zbuffer.fill(f32::INFINITY); // Restablece el búfer de profundidad.
+        self.overdraw.fill(0); // Reinicia el conteo de escrituras.
+    }
+
+    // Limpia el búfer con un degradado vertical, interpolando de `top` (primera fila) a `bottom` (última fila).
+    // Cada fila tiene un solo color, así que se calcula una vez y se copia a todos sus píxeles.
+    pub fn clear_gradient(&mut self, top: Color, bottom: Color) {
+        let last_row = self.height.saturating_sub(1).max(1) as f32;
+
+        for (y, row) in self.buffer.chunks_exact_mut(self.width * 4).enumerate() {
+            let color = top.lerp(bottom, y as f32 / last_row);
+            let rgba = [color.r, color.g, color.b, 255];
+            for pixel in row.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&rgba);
+            }
+        }
+        self.overdraw.fill(0); // Reinicia el conteo de escrituras.
+    }
+
+    // Divide el búfer en franjas horizontales de `rows` filas (la última puede ser más baja), cada una con las
+    // filas correspondientes de `depth`, que debe tener un valor por píxel, y la prueba `depth_func`.
+    // Las franjas no se solapan, así que cada una puede dibujarse en un hilo distinto.
+    pub fn tiles<'a>(
+        &'a mut self,
+        rows: usize,
+        depth: &'a mut [f32],
+        depth_func: DepthFunc,
+    ) -> Vec<FramebufferTile<'a>> {
+        let rows = rows.max(1);
+        let (width, height) = (self.width, self.height);
+        if width == 0 {
+            return Vec::new();
+        }
+
+        self.buffer
+            .chunks_mut(width * rows * 4)
+            .zip(depth.chunks_mut(width * rows))
+            .zip(self.overdraw.chunks_mut(width * rows))
+            .enumerate()
+            .map(|(i, ((buffer, zbuffer), overdraw))| FramebufferTile {
+                width,
+                y_start: i * rows,
+                y_end: (i * rows + rows).min(height),
+                buffer,
+                zbuffer,
+                overdraw,
+                depth_func,
+            })
+            .collect()
+    }
+
+    // Establece el color de un píxel en las coordenadas (x, y), sin prueba de profundidad: la geometría se dibuja
+    // con las franjas del renderizador. Cuenta como una escritura, así que los fondos ya no lo tapan.
+    // Devuelve verdadero si el píxel está dentro del búfer. El programa solo dibuja por franjas, así que esta
+    // función y las otras de un solo píxel de abajo por ahora solo las usan las pruebas.
+    #[cfg_attr(not(test), allow(dead_code))]
+    #[inline]
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) -> bool {
+        self.blend_pixel(x, y, color, 1.0, BlendMode::Replace)
     }
 
-    // Establece el color de un píxel en las coordenadas (x, y) si su profundidad es menor que la actual.
+    // Combina un color con el que ya tiene el píxel en (x, y) según `mode`, con opacidad `alpha`, sin prueba de
+    // profundidad. Devuelve verdadero si el píxel está dentro del búfer.
+    #[cfg_attr(not(test), allow(dead_code))]
     #[inline]
-    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color, alpha: f32, mode: BlendMode) -> bool {
         if x >= self.width || y >= self.height {
-            return; // No hace nada si las coordenadas están fuera de los límites.
+            return false;
         }
 
         let index = y * self.width + x;
+        let idx = index * 4;
+        let existing = Color::new(self.buffer[idx], self.buffer[idx + 1], self.buffer[idx + 2]);
+        let blended = mode.apply(existing, color, alpha);
+        self.buffer[idx..idx + 4].copy_from_slice(&[blended.r, blended.g, blended.b, 255]);
+        self.overdraw[index] = self.overdraw[index].saturating_add(1);
+        true
+    }
 
-        // Comprueba si el nuevo píxel está más cerca que el píxel existente.
-        if depth < self.zbuffer[index] {
-            self.zbuffer[index] = depth; // Actualiza el búfer de profundidad.
+    // Devuelve el color del píxel en (x, y), o `None` si las coordenadas están fuera del búfer.
+    #[cfg_attr(not(test), allow(dead_code))]
+    #[inline]
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let idx = (y * self.width + x) * 4;
+        Some(Color::new(self.buffer[idx], self.buffer[idx + 1], self.buffer[idx + 2]))
+    }
+
+    // Reemplaza el color de cada píxel por cuántas veces se escribió desde la última limpieza: negro si ninguna,
+    // y de azul (una) a rojo (`OVERDRAW_MAX` o más), pasando por cian, verde y amarillo.
+    pub fn overdraw_heatmap(&mut self) {
+        for (pixel, &count) in self.buffer.chunks_exact_mut(4).zip(&self.overdraw) {
+            let color = if count == 0 {
+                Color::BLACK
+            } else {
+                heat_color((count - 1) as f32 / (OVERDRAW_MAX - 1) as f32)
+            };
+            pixel[..3].copy_from_slice(&[color.r, color.g, color.b]);
+        }
+    }
+
+    // Dibuja una línea de (x0, y0) a (x1, y1) con el algoritmo de Bresenham, sin prueba de profundidad.
+    // La línea se recorta primero al rectángulo del búfer (Liang-Barsky), así que los extremos pueden quedar fuera.
+    // Las estelas usan `draw_background_line`, así que por ahora solo la usan las pruebas del recorte.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let max = (self.width as i32 - 1, self.height as i32 - 1);
+        plot_line(x0, y0, x1, y1, (0, 0), max, |x, y, _| self.put_pixel(x, y, color));
+    }
+
+    // Dibuja una línea como `draw_line`, pero solo en los píxeles donde no hay geometría, así queda detrás de todo
+    // como el fondo.
+    pub fn draw_background_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let max = (self.width as i32 - 1, self.height as i32 - 1);
+        plot_line(x0, y0, x1, y1, (0, 0), max, |x, y, _| self.set_background_pixel(x, y, color));
+    }
+
+    // Rellena el rectángulo de `width`×`height` píxeles con esquina superior izquierda en (x, y), sin prueba de
+    // profundidad. La parte fuera del búfer se recorta, así que el rectángulo puede salirse de la imagen.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let x0 = x.clamp(0, self.width as i32) as usize;
+        let y0 = y.clamp(0, self.height as i32) as usize;
+        let x1 = x.saturating_add(width.max(0)).clamp(0, self.width as i32) as usize;
+        let y1 = y.saturating_add(height.max(0)).clamp(0, self.height as i32) as usize;
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.put_pixel(px, py, color);
+            }
+        }
+    }
+
+    // Rellena el círculo de centro (cx, cy) y radio `radius`, sin prueba de profundidad. Un píxel está dentro si
+    // su distancia al centro no supera el radio; solo se recorre la parte del cuadrado envolvente dentro del búfer.
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32, color: Color) {
+        if radius < 0 {
+            return;
+        }
+
+        let radius_sq = radius as i64 * radius as i64;
+        let x0 = cx.saturating_sub(radius).clamp(0, self.width as i32);
+        let x1 = cx.saturating_add(radius).saturating_add(1).clamp(0, self.width as i32);
+        let y0 = cy.saturating_sub(radius).clamp(0, self.height as i32);
+        let y1 = cy.saturating_add(radius).saturating_add(1).clamp(0, self.height as i32);
+
+        for py in y0..y1 {
+            let dy = py as i64 - cy as i64;
+            for px in x0..x1 {
+                let dx = px as i64 - cx as i64;
+                if dx * dx + dy * dy <= radius_sq {
+                    self.put_pixel(px as usize, py as usize, color);
+                }
+            }
+        }
+    }
+
+    // Escribe `text` con la fuente de mapa de bits de 5×7, con la esquina superior izquierda del primer carácter
+    // en (x, y) y cada píxel de la fuente agrandado a un cuadrado de `scale`×`scale`. Los saltos de línea bajan
+    // a la línea siguiente. No usa prueba de profundidad y se recorta a los bordes del búfer.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Color, scale: usize) {
+        let scale = scale.max(1) as i32;
+        let advance = (GLYPH_WIDTH as i32 + 1) * scale; // Un píxel de separación entre caracteres.
+        let line_height = (GLYPH_HEIGHT as i32 + 2) * scale;
+
+        for (line_index, line) in text.lines().enumerate() {
+            let top = y + line_index as i32 * line_height;
+            for (char_index, c) in line.chars().enumerate() {
+                let left = x + char_index as i32 * advance;
+                for (row, bits) in glyph(c).iter().enumerate() {
+                    for column in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                            self.fill_rect(left + column as i32 * scale, top + row as i32 * scale, scale, scale, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Escribe un píxel dentro de los límites sin prueba de profundidad.
+    #[inline]
+    fn put_pixel(&mut self, x: usize, y: usize, color: Color) {
+        let idx = (y * self.width + x) * 4;
+        self.buffer[idx] = color.r;
+        self.buffer[idx + 1] = color.g;
+        self.buffer[idx + 2] = color.b;
+        self.buffer[idx + 3] = 255;
+    }
+
+    // Establece el color de un píxel solo si ahí no se ha dibujado geometría desde la última limpieza.
+    // Se usa para los fondos, que siempre deben quedar detrás de todo. Se revisa el conteo de escrituras y no la
+    // profundidad, porque los objetos translúcidos no escriben profundidad.
+    #[inline]
+    pub fn set_background_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let index = y * self.width + x;
+        if self.overdraw[index] == 0 {
             let idx = index * 4;
             self.buffer[idx] = color.r;
             self.buffer[idx + 1] = color.g;
             self.buffer[idx + 2] = color.b;
-            self.buffer[idx + 3] = 255; // El canal alfa se establece en 255.
+            self.buffer[idx + 3] = 255;
+        }
+    }
+
+    // Codifica en sRGB todo el búfer, tratando los colores almacenados como lineales.
+    // Se aplica una vez por fotograma después de dibujar; usa una tabla de 256 entradas por canal.
+    pub fn encode_srgb(&mut self) {
+        let mut table = [0u8; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            *entry = (linear_to_srgb(i as f32 / 255.0) * 255.0).round() as u8;
+        }
+
+        for pixel in self.buffer.chunks_exact_mut(4) {
+            pixel[0] = table[pixel[0] as usize];
+            pixel[1] = table[pixel[1] as usize];
+            pixel[2] = table[pixel[2] as usize];
         }
     }
 
+    // Reduce este búfer (renderizado a `factor` veces la resolución) al tamaño de `target`,
+    // promediando cada bloque de `factor`×`factor` píxeles (filtro de caja). El conteo de escrituras resultante
+    // es la suma del bloque, para que los fondos dibujados después no tapen los bordes suavizados ni los objetos
+    // translúcidos.
+    pub fn downsample_into(&self, target: &mut Framebuffer, factor: usize) {
+        let factor = factor.max(1);
+        if factor == 1 {
+            target.buffer.copy_from_slice(&self.buffer);
+            target.overdraw.copy_from_slice(&self.overdraw);
+            return;
+        }
+
+        let samples = (factor * factor) as u32;
+        for ty in 0..target.height {
+            for tx in 0..target.width {
+                let mut sum = [0u32; 3];
+                let mut writes = 0u16;
+
+                for sy in ty * factor..(ty + 1) * factor {
+                    for sx in tx * factor..(tx + 1) * factor {
+                        let index = sy * self.width + sx;
+                        let idx = index * 4;
+                        sum[0] += self.buffer[idx] as u32;
+                        sum[1] += self.buffer[idx + 1] as u32;
+                        sum[2] += self.buffer[idx + 2] as u32;
+                        writes = writes.saturating_add(self.overdraw[index]);
+                    }
+                }
+
+                let index = ty * target.width + tx;
+                let idx = index * 4;
+                target.buffer[idx] = (sum[0] / samples) as u8;
+                target.buffer[idx + 1] = (sum[1] / samples) as u8;
+                target.buffer[idx + 2] = (sum[2] / samples) as u8;
+                target.buffer[idx + 3] = 255;
+                target.overdraw[index] = writes;
+            }
+        }
+    }
+
+    // Aplica bloom: extrae los píxeles cuya luminancia supera `threshold`, los desenfoca con un filtro
+    // gaussiano separable (horizontal y luego vertical) de radio `blur_radius` y suma el resultado
+    // multiplicado por `intensity`. Debe llamarse después de dibujar toda la geometría.
+    pub fn bloom(&mut self, threshold: f32, blur_radius: usize, intensity: f32) {
+        let (width, height) = (self.width, self.height);
+        if width == 0 || height == 0 || blur_radius == 0 || intensity <= 0.0 {
+            return;
+        }
+
+        // Extrae la parte brillante de cada píxel. Se resta el umbral a la luminancia y se conserva el tono.
+        let mut bright: Vec<Vec3> = self
+            .buffer
+            .chunks_exact(4)
+            .map(|pixel| {
+                let color = Vec3::new(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32) / 255.0;
+                let luminance = 0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z;
+                if luminance > threshold {
+                    color * ((luminance - threshold) / luminance)
+                } else {
+                    Vec3::zeros()
+                }
+            })
+            .collect();
+
+        // Pesos gaussianos normalizados, con sigma igual a la mitad del radio.
+        let sigma = (blur_radius as f32 * 0.5).max(0.5);
+        let mut kernel: Vec<f32> = (0..=2 * blur_radius)
+            .map(|i| {
+                let d = i as f32 - blur_radius as f32;
+                (-(d * d) / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        let total: f32 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|w| *w /= total);
+
+        let radius = blur_radius as isize;
+        let mut blurred = vec![Vec3::zeros(); width * height];
+
+        // Pasada horizontal; los bordes repiten el último píxel.
+        for y in 0..height {
+            let row = y * width;
+            for x in 0..width {
+                let mut sum = Vec3::zeros();
+                for (k, weight) in kernel.iter().enumerate() {
+                    let sx = (x as isize + k as isize - radius).clamp(0, width as isize - 1) as usize;
+                    sum += bright[row + sx] * *weight;
+                }
+                blurred[row + x] = sum;
+            }
+        }
+
+        // Pasada vertical, reutilizando `bright` como destino.
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = Vec3::zeros();
+                for (k, weight) in kernel.iter().enumerate() {
+                    let sy = (y as isize + k as isize - radius).clamp(0, height as isize - 1) as usize;
+                    sum += blurred[sy * width + x] * *weight;
+                }
+                bright[y * width + x] = sum;
+            }
+        }
+
+        // Suma el resplandor a la imagen original.
+        for (pixel, glow) in self.buffer.chunks_exact_mut(4).zip(&bright) {
+            let existing = Color::new(pixel[0], pixel[1], pixel[2]);
+            let glowing = BlendMode::Add.apply(existing, Color::from_vec3(glow * intensity), 1.0);
+            pixel[..3].copy_from_slice(&[glowing.r, glowing.g, glowing.b]);
+        }
+    }
+
+    // Invierte el orden de las filas en su lugar (la primera pasa a ser la última), junto con el conteo de
+    // escrituras. Cada par de filas se intercambia una sola vez. Sirve para entregar la imagen a
+    // formatos o APIs que esperan las filas de abajo hacia arriba; `save_png` y `save_ppm` no la necesitan.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn flip_vertical(&mut self) {
+        fn swap_rows<T>(data: &mut [T], row_len: usize, rows: usize) {
+            for y in 0..rows / 2 {
+                let (top, bottom) = data.split_at_mut((rows - 1 - y) * row_len);
+                top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+            }
+        }
+
+        swap_rows(&mut self.buffer, self.width * 4, self.height);
+        swap_rows(&mut self.overdraw, self.width, self.height);
+    }
+
     // Devuelve una referencia al búfer de píxeles como un slice de bytes, para ser usado por Raylib.
     pub fn as_bytes(&self) -> &[u8] {
         &self.buffer
     }
+
+    // Guarda el contenido actual del búfer como una imagen PNG RGBA de 8 bits.
+    // Las filas ya están almacenadas de arriba hacia abajo, igual que en PNG, así que no hace falta invertirlas:
+    // las capturas salen del búfer y no de la textura de raylib.
+    pub fn save_png(&self, path: &str) -> Result<(), String> {
+        let image = image::RgbaImage::from_raw(self.width as u32, self.height as u32, self.buffer.clone())
+            .ok_or_else(|| "Framebuffer size does not match its pixel data".to_string())?;
+
+        image
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|e| format!("Error saving PNG '{}': {}", path, e))
+    }
+
+    // Guarda el contenido actual del búfer como PPM binario (P6): una cabecera de texto con el tamaño y el valor
+    // máximo, seguida de los bytes RGB de cada píxel fila por fila. No necesita codificador, así que es útil
+    // donde el PNG sobra; el canal alfa se descarta.
+    pub fn save_ppm(&self, path: &str) -> Result<(), String> {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.reserve(self.width * self.height * 3);
+        for pixel in self.buffer.chunks_exact(4) {
+            data.extend_from_slice(&pixel[..3]);
+        }
+
+        std::fs::write(path, data).map_err(|e| format!("Error saving PPM '{}': {}", path, e))
+    }
+}
+
+// Vista mutable de una franja de filas consecutivas del búfer, de `y_start` (incluida) a `y_end` (excluida).
+// Las coordenadas son las del búfer completo; los píxeles fuera de la franja se ignoran.
+pub struct FramebufferTile<'a> {
+    pub width: usize,       // Ancho del búfer completo en píxeles.
+    pub y_start: usize,     // Primera fila de la franja.
+    pub y_end: usize,       // Fila siguiente a la última de la franja.
+    buffer: &'a mut [u8],    // Píxeles RGBA de las filas de la franja.
+    zbuffer: &'a mut [f32],  // Profundidad de las filas de la franja, tomada del `Renderer`.
+    overdraw: &'a mut [u16], // Conteo de escrituras de las filas de la franja.
+    depth_func: DepthFunc,   // Función de comparación del renderizador.
+}
+
+impl FramebufferTile<'_> {
+    // Devuelve el índice del píxel dentro de la franja, o `None` si queda fuera de ella.
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y < self.y_start || y >= self.y_end {
+            return None;
+        }
+        Some((y - self.y_start) * self.width + x)
+    }
+
+    // Establece el color de un píxel si pasa la prueba de profundidad contra el valor almacenado, y guarda su
+    // profundidad. Devuelve verdadero si el píxel se escribió; nada se escribe fuera de la franja.
+    #[inline]
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool {
+        let Some(index) = self.index(x, y) else {
+            return false; // No hace nada si las coordenadas están fuera de la franja.
+        };
+
+        // Comprueba el nuevo píxel contra el existente usando la función de profundidad configurada.
+        if self.depth_func.test(depth, self.zbuffer[index]) {
+            self.zbuffer[index] = depth; // Actualiza el búfer de profundidad.
+            let idx = index * 4;
+            self.buffer[idx] = color.r;
+            self.buffer[idx + 1] = color.g;
+            self.buffer[idx + 2] = color.b;
+            self.buffer[idx + 3] = 255; // El canal alfa se establece en 255.
+            self.overdraw[index] = self.overdraw[index].saturating_add(1);
+            return true;
+        }
+        false
+    }
+
+    // Combina un color con el que ya tiene el píxel según `mode`, si pasa la prueba de profundidad. No escribe
+    // la profundidad, para que los fragmentos parciales no tapen a los que se dibujen después.
+    // Devuelve verdadero si el píxel se escribió.
+    #[inline]
+    pub fn blend_pixel(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: Color,
+        alpha: f32,
+        depth: f32,
+        mode: BlendMode,
+    ) -> bool {
+        let Some(index) = self.index(x, y) else {
+            return false;
+        };
+
+        if self.depth_func.test(depth, self.zbuffer[index]) {
+            let idx = index * 4;
+            let existing = Color::new(self.buffer[idx], self.buffer[idx + 1], self.buffer[idx + 2]);
+            let blended = mode.apply(existing, color, alpha);
+            self.buffer[idx..idx + 3].copy_from_slice(&[blended.r, blended.g, blended.b]);
+            self.overdraw[index] = self.overdraw[index].saturating_add(1);
+            return true;
+        }
+        false
+    }
+}
+
+// Recorta el segmento de (x0, y0) a (x1, y1) al rectángulo de esquinas `min` y `max` (incluidas) y recorre con
+// Bresenham los píxeles que quedan dentro. Llama a `plot` con cada píxel y su posición sobre el segmento completo,
+// de 0.0 en (x0, y0) a 1.0 en (x1, y1), para interpolar atributos aunque el recorte quite parte de la línea.
+// Lo usan el búfer completo y las franjas del wireframe, que recortan a sus propias filas.
+pub fn plot_line(
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+    min: (i32, i32),
+    max: (i32, i32),
+    mut plot: impl FnMut(usize, usize, f32),
+) {
+    let Some((mut x, mut y, end_x, end_y)) = clip_line(x0, y0, x1, y1, min, max) else {
+        return;
+    };
+
+    // La posición de cada píxel se proyecta sobre el segmento original; se calcula con flotantes porque los
+    // extremos pueden estar muy lejos de la pantalla.
+    let (fx0, fy0) = (x0 as f32, y0 as f32);
+    let (line_x, line_y) = (x1 as f32 - fx0, y1 as f32 - fy0);
+    let length_sq = line_x * line_x + line_y * line_y;
+    let position = |x: i32, y: i32| {
+        if length_sq == 0.0 {
+            return 0.0;
+        }
+        (((x as f32 - fx0) * line_x + (y as f32 - fy0) * line_y) / length_sq).clamp(0.0, 1.0)
+    };
+
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let sx = if x < end_x { 1 } else { -1 };
+    let sy = if y < end_y { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        plot(x as usize, y as usize, position(x, y));
+
+        if x == end_x && y == end_y {
+            break;
+        }
+
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+// Recorta un segmento al rectángulo de esquinas `min` y `max` (Liang-Barsky). Devuelve los extremos recortados, o
+// `None` si el segmento queda completamente fuera o el rectángulo está vacío.
+fn clip_line(x0: i32, y0: i32, x1: i32, y1: i32, min: (i32, i32), max: (i32, i32)) -> Option<(i32, i32, i32, i32)> {
+    if max.0 < min.0 || max.1 < min.1 {
+        return None;
+    }
+
+    let (min_x, min_y, max_x, max_y) = (min.0 as f32, min.1 as f32, max.0 as f32, max.1 as f32);
+    let (fx0, fy0) = (x0 as f32, y0 as f32);
+    let (dx, dy) = (x1 as f32 - fx0, y1 as f32 - fy0);
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+
+    // Cada par (p, q) representa un borde: el segmento está dentro donde p * t <= q.
+    for (p, q) in [(-dx, fx0 - min_x), (dx, max_x - fx0), (-dy, fy0 - min_y), (dy, max_y - fy0)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None; // Paralelo al borde y fuera de él.
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    let clamp_x = |v: f32| v.round().clamp(min_x, max_x) as i32;
+    let clamp_y = |v: f32| v.round().clamp(min_y, max_y) as i32;
+    Some((
+        clamp_x(fx0 + dx * t0),
+        clamp_y(fy0 + dy * t0),
+        clamp_x(fx0 + dx * t1),
+        clamp_y(fy0 + dy * t1),
+    ))
+}
+
+// Color de un mapa de calor para `t` entre 0.0 y 1.0: azul, cian, verde, amarillo y rojo.
+fn heat_color(t: f32) -> Color {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+
+    let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let i = (scaled as usize).min(STOPS.len() - 2);
+    let f = scaled - i as f32;
+    let [a, b] = [STOPS[i], STOPS[i + 1]].map(|[r, g, b]| Vec3::new(r, g, b));
+    Color::from_vec3(a + (b - a) * f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lerp_returns_the_endpoints_and_the_rounded_midpoint() {
+        let (a, b) = (Color::new(0, 100, 255), Color::new(255, 201, 0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        // 127.5 y 150.5 se redondean hacia arriba.
+        assert_eq!(a.lerp(b, 0.5), Color::new(128, 151, 128));
+        // Fuera de [0, 1] se queda en los extremos.
+        assert_eq!(a.lerp(b, -1.0), a);
+        assert_eq!(a.lerp(b, 2.0), b);
+    }
+
+    #[test]
+    fn add_saturates_each_channel_at_255() {
+        assert_eq!(Color::new(10, 20, 30) + Color::new(1, 2, 3), Color::new(11, 22, 33));
+        assert_eq!(Color::new(200, 255, 0) + Color::new(100, 1, 0), Color::new(255, 255, 0));
+        assert_eq!(Color::WHITE + Color::WHITE, Color::WHITE);
+    }
+
+    #[test]
+    fn from_hex_reads_the_three_formats() {
+        assert_eq!(Color::from_hex("#F80"), Ok(Color::new(255, 136, 0)));
+        assert_eq!(Color::from_hex("#1a2B3c"), Ok(Color::new(0x1A, 0x2B, 0x3C)));
+        // El alfa se acepta pero se descarta.
+        assert_eq!(Color::from_hex("#1A2B3C80"), Ok(Color::new(0x1A, 0x2B, 0x3C)));
+    }
+
+    #[test]
+    fn from_hex_rejects_invalid_strings() {
+        for text in ["F80", "#F8", "#FF88", "#GG8800", "#FF8800A", "", "#"] {
+            let error = Color::from_hex(text).unwrap_err();
+            assert!(error.contains("invalid color"), "{}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn to_hex_round_trips_through_from_hex() {
+        assert_eq!(Color::new(255, 136, 0).to_hex(), "#FF8800");
+        for color in [Color::BLACK, Color::WHITE, Color::new(0x1A, 0x2B, 0x3C), Color::new(7, 128, 254)] {
+            assert_eq!(Color::from_hex(&color.to_hex()), Ok(color));
+        }
+    }
+
+    #[test]
+    fn linear_half_encodes_to_188() {
+        assert_eq!((linear_to_srgb(0.5) * 255.0).round(), 188.0);
+        assert_eq!(linear_to_srgb(0.0), 0.0);
+        assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-6);
+
+        // `encode_srgb` usa la misma curva sobre los bytes del búfer y no toca el alfa.
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.clear(Color::new(128, 0, 255));
+        framebuffer.encode_srgb();
+        assert_eq!(framebuffer.as_bytes(), &[188, 0, 255, 255]);
+    }
+
+    #[test]
+    fn adding_two_half_grays_gives_full_gray() {
+        let half = Color::new(128, 128, 128);
+        assert_eq!(BlendMode::Add.apply(half, half, 1.0), Color::WHITE);
+
+        // El mismo resultado al mezclar en el búfer.
+        let mut framebuffer = Framebuffer::new(2, 2);
+        framebuffer.clear(half);
+        assert!(framebuffer.blend_pixel(1, 0, half, 1.0, BlendMode::Add));
+        assert_eq!(framebuffer.get_pixel(1, 0), Some(Color::WHITE));
+        assert_eq!(framebuffer.get_pixel(0, 0), Some(half));
+    }
+
+    #[test]
+    fn replace_ignores_alpha_and_the_existing_color() {
+        let (dst, src) = (Color::new(200, 100, 255), Color::new(10, 20, 30));
+        assert_eq!(BlendMode::Replace.apply(dst, src, 1.0), src);
+        assert_eq!(BlendMode::Replace.apply(dst, src, 0.0), src);
+
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.clear(dst);
+        assert!(framebuffer.blend_pixel(0, 0, src, 0.25, BlendMode::Replace));
+        assert_eq!(framebuffer.get_pixel(0, 0), Some(src));
+    }
+
+    #[test]
+    fn multiply_darkens_by_the_source_color() {
+        let dst = Color::new(200, 100, 255);
+        assert_eq!(BlendMode::Multiply.apply(dst, Color::WHITE, 1.0), dst);
+        assert_eq!(BlendMode::Multiply.apply(dst, Color::BLACK, 1.0), Color::BLACK);
+        assert_eq!(BlendMode::Multiply.apply(dst, Color::new(128, 128, 128), 1.0), Color::new(100, 50, 128));
+        // Con alfa cero no cambia nada.
+        assert_eq!(BlendMode::Multiply.apply(dst, Color::BLACK, 0.0), dst);
+    }
+
+    #[test]
+    fn depth_funcs_compare_against_the_stored_depth() {
+        let cases = [
+            (DepthFunc::Less, [true, false, false]),
+            (DepthFunc::LessEqual, [true, true, false]),
+            (DepthFunc::Greater, [false, false, true]),
+            (DepthFunc::Always, [true, true, true]),
+        ];
+        // Fragmentos más cerca, a la misma profundidad y más lejos que el valor almacenado de 0.5.
+        for (func, expected) in cases {
+            assert_eq!([0.25, 0.5, 0.75].map(|depth| func.test(depth, 0.5)), expected, "{:?}", func);
+        }
+        // En un píxel vacío todas pasan, incluida `Greater`.
+        assert!(cases.iter().all(|(func, _)| func.test(0.5, f32::INFINITY)));
+        // `next` recorre las cuatro y vuelve a la primera.
+        let cycle: Vec<_> = std::iter::successors(Some(DepthFunc::Less), |func| Some(func.next())).take(5).collect();
+        assert_eq!(cycle, [DepthFunc::Less, DepthFunc::LessEqual, DepthFunc::Greater, DepthFunc::Always, DepthFunc::Less]);
+    }
+
+    #[test]
+    fn set_pixel_then_get_pixel_reads_it_back() {
+        let mut framebuffer = Framebuffer::new(4, 3);
+        let color = Color::new(10, 200, 30);
+        assert!(framebuffer.set_pixel(3, 2, color));
+        assert_eq!(framebuffer.get_pixel(3, 2), Some(color));
+        assert_eq!(framebuffer.get_pixel(2, 2), Some(Color::BLACK));
+
+        // Fuera del búfer no se escribe ni se lee nada.
+        assert!(!framebuffer.set_pixel(4, 0, color));
+        assert_eq!(framebuffer.get_pixel(4, 0), None);
+        assert_eq!(framebuffer.get_pixel(0, 3), None);
+    }
+
+    #[test]
+    fn fill_rect_and_fill_circle_cover_only_their_inside() {
+        let color = Color::new(255, 0, 0);
+        let mut framebuffer = Framebuffer::new(10, 10);
+        // Se sale por la izquierda: cubre las columnas 0 y 1 de las filas 2 a 4.
+        framebuffer.fill_rect(-3, 2, 5, 3, color);
+        assert_eq!(framebuffer.get_pixel(0, 2), Some(color));
+        assert_eq!(framebuffer.get_pixel(1, 4), Some(color));
+        assert_eq!(framebuffer.get_pixel(2, 3), Some(Color::BLACK));
+        assert_eq!(framebuffer.get_pixel(0, 1), Some(Color::BLACK));
+        assert_eq!(framebuffer.get_pixel(0, 5), Some(Color::BLACK));
+
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.fill_circle(5, 5, 3, color);
+        for (x, y) in [(5, 5), (8, 5), (5, 2), (7, 7)] {
+            assert_eq!(framebuffer.get_pixel(x, y), Some(color), "({}, {}) debería estar dentro", x, y);
+        }
+        // (8, 8) está a 4.2 del centro, fuera del radio aunque dentro del cuadrado envolvente.
+        for (x, y) in [(9, 5), (5, 1), (8, 8), (2, 2)] {
+            assert_eq!(framebuffer.get_pixel(x, y), Some(Color::BLACK), "({}, {}) debería estar fuera", x, y);
+        }
+
+        // Un círculo que se sale del búfer se recorta sin entrar en pánico.
+        framebuffer.fill_circle(-1, 9, 2, color);
+        assert_eq!(framebuffer.get_pixel(0, 9), Some(color));
+    }
+
+    #[test]
+    fn draw_line_clips_a_diagonal_with_endpoints_outside() {
+        let color = Color::new(0, 255, 0);
+        let mut framebuffer = Framebuffer::new(5, 5);
+        framebuffer.draw_line(-2, -2, 6, 6, color);
+        for i in 0..5 {
+            assert_eq!(framebuffer.get_pixel(i, i), Some(color), "({}, {})", i, i);
+        }
+        assert_eq!(framebuffer.get_pixel(2, 2), Some(color)); // Punto medio.
+        assert_eq!(framebuffer.get_pixel(1, 0), Some(Color::BLACK));
+        assert_eq!(framebuffer.get_pixel(0, 1), Some(Color::BLACK));
+
+        // Una línea que no cruza el búfer no dibuja nada.
+        let mut framebuffer = Framebuffer::new(5, 5);
+        framebuffer.draw_line(-3, 0, 0, -3, color);
+        framebuffer.draw_line(6, -1, 6, 10, color);
+        assert!(framebuffer.as_bytes().chunks_exact(4).all(|pixel| pixel[..3] == [0, 0, 0]));
+    }
+
+    #[test]
+    fn clear_gradient_interpolates_from_the_top_row_to_the_bottom_row() {
+        let (top, bottom) = (Color::new(0, 100, 200), Color::new(200, 100, 0));
+        let mut framebuffer = Framebuffer::new(3, 5);
+        framebuffer.set_pixel(1, 1, Color::WHITE);
+        framebuffer.clear_gradient(top, bottom);
+
+        for x in 0..3 {
+            assert_eq!(framebuffer.get_pixel(x, 0), Some(top));
+            assert_eq!(framebuffer.get_pixel(x, 2), Some(Color::new(100, 100, 100)));
+            assert_eq!(framebuffer.get_pixel(x, 4), Some(bottom));
+        }
+        // También reinicia el conteo de escrituras.
+        assert!(framebuffer.overdraw.iter().all(|&count| count == 0));
+    }
+
+    #[test]
+    fn resize_matches_the_new_size_and_clears_to_black() {
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.clear(Color::WHITE);
+        framebuffer.set_pixel(0, 0, Color::WHITE);
+
+        for (width, height) in [(8, 3), (2, 2), (0, 5)] {
+            framebuffer.resize(width, height);
+            assert_eq!(framebuffer.as_bytes().len(), width * height * 4);
+            assert_eq!(framebuffer.overdraw.len(), width * height);
+            assert!(framebuffer.as_bytes().chunks_exact(4).all(|pixel| pixel == [0, 0, 0, 255]));
+        }
+    }
+
+    #[test]
+    fn draw_text_lights_the_pixels_of_the_glyph() {
+        let mut framebuffer = Framebuffer::new(12, 16);
+        framebuffer.draw_text(0, 0, "1", Color::WHITE, 2);
+
+        // El "1" tiene 10 píxeles encendidos en la fuente; con escala 2 cada uno ocupa 2×2.
+        let lit = framebuffer.as_bytes().chunks_exact(4).filter(|pixel| pixel[..3] == [255, 255, 255]).count();
+        assert_eq!(lit, 40);
+        // La primera fila del "1" solo enciende la columna central (x = 4 y 5 con escala 2).
+        for x in 0..10 {
+            let expected = if x == 4 || x == 5 { Color::WHITE } else { Color::BLACK };
+            assert_eq!(framebuffer.get_pixel(x, 0), Some(expected), "x = {}", x);
+        }
+        // La base ocupa las columnas 1 a 3 de la última fila.
+        assert_eq!(framebuffer.get_pixel(2, 13), Some(Color::WHITE));
+        assert_eq!(framebuffer.get_pixel(8, 13), Some(Color::BLACK));
+
+        // Las minúsculas se dibujan igual que las mayúsculas.
+        let mut upper = Framebuffer::new(6, 7);
+        let mut lower = Framebuffer::new(6, 7);
+        upper.draw_text(0, 0, "A", Color::WHITE, 1);
+        lower.draw_text(0, 0, "a", Color::WHITE, 1);
+        assert_eq!(upper.as_bytes(), lower.as_bytes());
+    }
+
+    #[test]
+    fn save_ppm_writes_the_header_and_rgb_bytes() {
+        let mut framebuffer = Framebuffer::new(2, 2);
+        framebuffer.clear(Color::new(1, 2, 3));
+        framebuffer.set_pixel(1, 1, Color::new(250, 251, 252));
+
+        let path = std::env::temp_dir().join(format!("lab4_save_ppm_{}.ppm", std::process::id()));
+        let path = path.to_string_lossy();
+        framebuffer.save_ppm(&path).expect("image should be written");
+        let data = std::fs::read(path.as_ref());
+        let _ = std::fs::remove_file(path.as_ref());
+        let data = data.expect("written file should be readable");
+
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(data.len(), header.len() + 2 * 2 * 3);
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(&data[header.len()..header.len() + 3], &[1, 2, 3]);
+        assert_eq!(&data[data.len() - 3..], &[250, 251, 252]);
+    }
+
+    #[test]
+    fn plot_line_reports_the_position_on_the_unclipped_segment() {
+        let mut visited = Vec::new();
+        plot_line(0, 0, 10, 0, (0, 0), (4, 4), |x, y, t| visited.push((x, y, t)));
+        assert_eq!(visited.len(), 5);
+        assert_eq!(visited[0], (0, 0, 0.0));
+        assert_eq!(visited[4], (4, 0, 0.4));
+
+        // Recortada a las filas 3 a 5, como una franja del búfer.
+        let mut visited = Vec::new();
+        plot_line(2, 0, 2, 9, (0, 3), (5, 5), |x, y, t| visited.push((x, y, t)));
+        let rows: Vec<_> = visited.iter().map(|&(x, y, _)| (x, y)).collect();
+        assert_eq!(rows, [(2, 3), (2, 4), (2, 5)]);
+        assert!((visited[0].2 - 3.0 / 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flip_vertical_swaps_rows_and_twice_restores_the_buffer() {
+        // Alto impar: la fila del medio se queda en su lugar.
+        let mut framebuffer = Framebuffer::new(2, 3);
+        // La fila `y` se escribe `y` veces, para que el conteo de escrituras también la distinga.
+        for y in 0..3 {
+            for _ in 0..y {
+                framebuffer.set_pixel(0, y, Color::new(y as u8 * 100, 0, 0));
+            }
+        }
+        let buffer = framebuffer.buffer.clone();
+        let overdraw = framebuffer.overdraw.clone();
+
+        framebuffer.flip_vertical();
+        for y in 0..3 {
+            assert_eq!(framebuffer.overdraw[y * 2], (2 - y) as u16);
+        }
+        // La fila 0 nunca se escribió y queda negra al final.
+        assert_eq!(framebuffer.get_pixel(0, 0), Some(Color::new(200, 0, 0)));
+        assert_eq!(framebuffer.get_pixel(0, 1), Some(Color::new(100, 0, 0)));
+        assert_eq!(framebuffer.get_pixel(0, 2), Some(Color::BLACK));
+
+        framebuffer.flip_vertical();
+        assert_eq!(framebuffer.buffer, buffer);
+        assert_eq!(framebuffer.overdraw, overdraw);
+    }
+
+    #[test]
+    fn tiles_test_and_write_the_given_depth() {
+        let mut framebuffer = Framebuffer::new(2, 3);
+        let mut depth = vec![f32::INFINITY; 6];
+        let color = Color::new(10, 200, 30);
+        let mut tiles = framebuffer.tiles(2, &mut depth, DepthFunc::Less);
+        assert_eq!(tiles.len(), 2);
+
+        // Cada franja solo escribe en sus filas.
+        assert!(!tiles[0].set_pixel(1, 2, color, 0.5));
+        assert!(tiles[1].set_pixel(1, 2, color, 0.5));
+        // Un fragmento más lejano no pasa la prueba de profundidad y el píxel conserva su color.
+        assert!(!tiles[1].set_pixel(1, 2, Color::WHITE, 0.9));
+        // La mezcla prueba la profundidad pero no la escribe.
+        assert!(tiles[0].blend_pixel(0, 0, color, 1.0, 0.5, BlendMode::Replace));
+
+        assert_eq!(framebuffer.get_pixel(1, 2), Some(color));
+        assert_eq!(framebuffer.get_pixel(0, 0), Some(color));
+        assert_eq!(depth[5], 0.5);
+        assert_eq!(depth[0], f32::INFINITY);
+    }
 }
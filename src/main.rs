@@ -1,22 +1,131 @@
 // Importaciones de módulos locales para organizar el código.
+mod camera; // Define la cámara orbital y sus proyecciones.
+mod clock; // Lleva el tiempo de la simulación, con pausa y velocidad.
+mod font; // Fuente de mapa de bits para escribir texto en el búfer.
 mod framebuffer; // Maneja el búfer de fotogramas para dibujar píxeles.
 mod mesh; // Define estructuras y funciones para manejar mallas de objetos 3D.
+mod orbit; // Define las órbitas de los objetos alrededor de su padre.
 mod renderer; // Contiene la lógica de renderizado principal.
+mod scene; // Carga las escenas desde un archivo TOML.
+mod shadow; // Mapa de sombras de la luz principal.
 mod shaders; // Define los sombreadores para diferentes apariencias de planetas.
+mod starfield; // Dibuja el fondo de estrellas.
+mod texture; // Carga y muestrea texturas de imagen.
 
 // Usamos tipos y funciones de los módulos importados y de bibliotecas externas.
-use framebuffer::{Color, Framebuffer}; // Para colores y el búfer de fotogramas.
+use camera::{Camera, CameraMode, Projection}; // Para la cámara orbital o libre y el tipo de proyección.
+use clap::{CommandFactory, Parser}; // Para leer los argumentos de la línea de comandos.
+use clock::SimClock; // Para el tiempo de la animación.
+use framebuffer::{BlendMode, BloomSettings, Color, Framebuffer}; // Para colores y el búfer de fotogramas.
 use mesh::ObjMesh; // Para la estructura de mallas de objetos.
-use nalgebra_glm::{look_at, perspective, rotate, Mat4, Vec3}; // Para matemáticas de gráficos 3D.
+use nalgebra_glm::{rotate, rotate_vec3, Mat4, Vec3}; // Para matemáticas de gráficos 3D.
+use orbit::{orbit_point, orbit_position, Orbit}; // Para las órbitas de planetas y lunas.
 use raylib::prelude::*; // Para la creación de la ventana y manejo de eventos.
-use renderer::Renderer; // El renderizador que dibujará todo.
+use renderer::{DebugView, DrawCall, RenderStats, Renderer, Winding}; // El renderizador que dibujará todo.
 use shaders::*; // Importa todos los sombreadores definidos.
+use shadow::ShadowMap; // Para las sombras de los objetos opacos.
+use starfield::Starfield; // Para el fondo de estrellas.
+use std::path::Path; // Para armar las rutas de los cuadros de una secuencia.
+use std::time::{Instant, SystemTime, UNIX_EPOCH}; // Para medir el dibujo y nombrar las capturas con una marca de tiempo.
 
-// Constantes para el tamaño de la ventana.
-const WIDTH: usize = 800; // Ancho de la ventana en píxeles.
-const HEIGHT: usize = 600; // Alto de la ventana en píxeles.
+// Valores por defecto del tamaño de la ventana y la tasa de cuadros; se pueden cambiar desde la línea de comandos.
+const WIDTH: usize = 800; // Ancho inicial de la ventana en píxeles.
+const HEIGHT: usize = 600; // Alto inicial de la ventana en píxeles.
+const FPS: u32 = 60; // Cuadros por segundo objetivo.
 
-// Estructura que representa un objeto que se puede renderizar en la escena.
+// Factor de supermuestreo (SSAA): se dibuja a SUPERSAMPLE² veces los píxeles y se promedia al mostrar.
+// Cada píxel interno ocupa 10 bytes (color RGBA + profundidad + conteo de escrituras), así que a 800×600 el búfer
+// interno usa unos 4.8 MB con factor 1, 19.2 MB con factor 2 y 43.2 MB con factor 3; el costo de dibujo crece igual.
+const SUPERSAMPLE: usize = 1;
+
+// Archivo opcional con la descripción de las escenas. Si no existe se usan las escenas integradas.
+const SCENE_PATH: &str = "scene.toml";
+
+// Argumentos de la línea de comandos. Sin argumentos se usa la configuración de siempre.
+#[derive(Parser, Debug)]
+#[command(about = "Renderizador por software de planetas")]
+struct Args {
+    /// Ancho inicial de la ventana en píxeles.
+    #[arg(long, default_value_t = WIDTH, value_parser = parse_positive)]
+    width: usize,
+
+    /// Alto inicial de la ventana en píxeles.
+    #[arg(long, default_value_t = HEIGHT, value_parser = parse_positive)]
+    height: usize,
+
+    /// Cuadros por segundo objetivo.
+    #[arg(long, default_value_t = FPS, value_parser = clap::value_parser!(u32).range(1..))]
+    fps: u32,
+
+    /// Escena inicial, numerada desde 1 como en las teclas.
+    #[arg(long, default_value_t = 1, value_parser = parse_positive)]
+    scene: usize,
+
+    /// Dibuja un solo cuadro sin abrir la ventana y lo guarda en `--out`.
+    #[arg(long)]
+    headless: bool,
+
+    /// Archivo PNG donde se guarda el cuadro en modo sin ventana; con extensión `.ppm` se guarda como PPM.
+    #[arg(long, default_value = "frame.png", requires = "headless")]
+    out: String,
+
+    /// Tiempo de simulación en segundos del cuadro dibujado en modo sin ventana; en una secuencia, el del primero.
+    #[arg(long, default_value_t = 0.0, requires = "headless")]
+    time: f32,
+
+    /// Guarda una secuencia de este número de cuadros en `--out-dir` en vez de un solo cuadro.
+    #[arg(long, requires = "headless", value_parser = parse_positive)]
+    frames: Option<usize>,
+
+    /// Tiempo de simulación total de la secuencia en segundos. Por defecto, una vuelta de un objeto con velocidad 1.
+    #[arg(long, default_value_t = std::f32::consts::TAU, requires = "frames")]
+    duration: f32,
+
+    /// Carpeta donde se guardan los cuadros `frame_0000.png`, `frame_0001.png`, ... de la secuencia.
+    #[arg(long, default_value = "frames", requires = "frames")]
+    out_dir: String,
+
+    /// Escribe en los cuadros del modo sin ventana el nombre de la escena, el tiempo y cuánto tardó en dibujarse.
+    #[arg(long, requires = "headless")]
+    label: bool,
+
+    /// Dibuja en un solo hilo en vez de repartir las franjas del búfer entre varios; el resultado es el mismo.
+    #[arg(long)]
+    serial: bool,
+
+    /// Dibuja la escena este número de cuadros sin guardarlos, en paralelo, en un solo hilo y en un solo hilo sin
+    /// repartir los triángulos en franjas, e informa el tiempo medio por cuadro de cada camino.
+    #[arg(long, requires = "headless", conflicts_with = "frames", value_parser = parse_positive)]
+    bench: Option<usize>,
+
+    /// Puntos con que se aproxima cada órbita al dibujar las estelas (tecla T).
+    #[arg(long, default_value_t = 128, value_parser = clap::value_parser!(u32).range(3..))]
+    orbit_samples: u32,
+
+    /// Resolución en texeles del mapa de sombras de la luz principal (tecla J); 0 lo desactiva y deja solo las
+    /// sombras analíticas de lunas y anillos. El mapa vuelve a dibujar los objetos opacos desde la luz en cada
+    /// cuadro, lo que en las escenas incluidas añade del orden de un 10 a 20 % al tiempo por cuadro.
+    #[arg(long, default_value_t = 1024)]
+    shadow_map_size: usize,
+
+    /// Guarda las mallas de los objetos de `--scene` en esta carpeta como `mesh_00.obj`, `mesh_01.obj`, ...
+    /// (padres antes que hijos, sin transformar) y termina sin dibujar. Sirve para revisar los generadores
+    /// procedurales en otro programa.
+    #[arg(long)]
+    dump_obj: Option<String>,
+}
+
+// Lee un entero mayor que cero.
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be greater than zero".to_string()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Estructura que representa un objeto que se puede renderizar en la escena. Es también un nodo del grafo de
+// escena: `position` es relativa al padre y los hijos se mueven con él, como una luna con su planeta.
 struct RenderObject {
     mesh: ObjMesh, // La malla 3D del objeto.
     shader: Box<dyn PlanetShader>, // El sombreador que define cómo se colorea el objeto.
@@ -24,6 +133,14 @@ struct RenderObject {
     scale: f32, // El tamaño del objeto.
     rotation_speed: f32, // La velocidad a la que rota el objeto.
     rotation_axis: Vec3, // El eje sobre el cual rota el objeto.
+    axial_tilt: f32, // Inclinación fija del eje en radianes, hacia +Z, aplicada antes de la rotación.
+    double_sided: bool, // Si es verdadero, se dibujan ambas caras (sin backface culling).
+    back_faces_only: bool, // Si es verdadero, se dibujan solo las caras traseras (capas de atmósfera).
+    opaque: bool, // Si es falso, se dibuja después de los opacos, de atrás hacia adelante y sin escribir profundidad.
+    blend: BlendMode, // Cómo se combina con lo que tiene detrás; solo se usa si no es opaco.
+    material: Option<Material>, // Coeficientes de iluminación; `None` usa los del sombreador.
+    orbit: Option<Orbit>, // Si tiene una, `position` se recalcula cada cuadro sobre la órbita alrededor del padre.
+    children: Vec<RenderObject>, // Objetos que heredan la posición de este.
 }
 
 impl RenderObject {
@@ -41,15 +158,47 @@ impl RenderObject {
             scale,
             rotation_speed: 1.0, // Velocidad de rotación por defecto.
             rotation_axis: Vec3::new(0.0, 1.0, 0.0), // Eje de rotación por defecto (eje Y).
+            axial_tilt: 0.0, // Sin inclinación, el eje apunta hacia arriba.
+            double_sided: false, // Por defecto solo se dibujan las caras frontales.
+            back_faces_only: false,
+            opaque: true,
+            blend: BlendMode::Alpha,
+            material: None,
+            orbit: None,
+            children: Vec::new(),
         }
     }
 
-    // Calcula y devuelve la matriz de modelo para este objeto, que incluye traslación, rotación y escala.
-    fn get_model_matrix(&self, time: f32) -> Mat4 {
-        let mut transform = Mat4::identity(); // Empezamos con una matriz de identidad.
+    // Devuelve el marco que heredan los hijos: el del padre (`parent`) trasladado a la posición de este objeto.
+    // La rotación y la escala no se heredan, para que una luna no gire alrededor del planeta al ritmo de su
+    // rotación ni cambie de tamaño con él.
+    fn get_frame_matrix(&self, parent: &Mat4) -> Mat4 {
+        nalgebra_glm::translate(parent, &self.position)
+    }
+
+    // Devuelve la inclinación fija del eje: una rotación alrededor de X que lleva el polo hacia +Z, es decir,
+    // hacia la cámara inicial, como se ve Saturno desde la Tierra.
+    fn tilt(&self, transform: &Mat4) -> Mat4 {
+        rotate(transform, self.axial_tilt, &Vec3::new(1.0, 0.0, 0.0))
+    }
+
+    // Devuelve el marco del cuerpo dentro de `parent`: la matriz de modelo sin el giro animado. Los sombreadores
+    // calculan sus patrones en este marco, así que siguen la inclinación del eje.
+    fn get_body_matrix(&self, parent: &Mat4) -> Mat4 {
+        let tilted = self.tilt(&self.get_frame_matrix(parent));
+        nalgebra_glm::scale(&tilted, &Vec3::new(self.scale, self.scale, self.scale))
+    }
 
+    // Calcula y devuelve la matriz de modelo para este objeto dentro del marco `parent`, que incluye traslación,
+    // rotación y escala. Para un objeto sin padre, `parent` es la identidad.
+    fn get_model_matrix(&self, parent: &Mat4, time: f32) -> Mat4 {
         // Aplicamos la traslación para mover el objeto a su posición.
-        transform = nalgebra_glm::translate(&transform, &self.position);
+        let mut transform = self.get_frame_matrix(parent);
+
+        // Inclinamos el eje con una rotación fija, así el polo apunta siempre al mismo lado mientras el objeto gira.
+        // La matriz de normales del renderizador es la inversa transpuesta del modelo completo, así que las normales
+        // reciben la inclinación y el giro combinados.
+        transform = self.tilt(&transform);
 
         // Aplicamos la rotación, que cambia con el tiempo para animar el objeto.
         transform = rotate(&transform, time * self.rotation_speed, &self.rotation_axis);
@@ -61,24 +210,240 @@ impl RenderObject {
     }
 }
 
+// Mueve los objetos con órbita, y sus hijos, a su posición en el tiempo `time`.
+fn update_orbits(objects: &mut [RenderObject], time: f32) {
+    for obj in objects {
+        if let Some(orbit) = &obj.orbit {
+            obj.position = orbit_position(orbit, time);
+        }
+        update_orbits(&mut obj.children, time);
+    }
+}
+
+// Reemplaza las mallas de los objetos, y las de sus hijos, por copias facetadas con una normal por cara.
+fn facet_meshes(objects: &mut [RenderObject]) {
+    for obj in objects {
+        obj.mesh = obj.mesh.make_flat();
+        facet_meshes(&mut obj.children);
+    }
+}
+
+// Devuelve el radio de una esfera centrada en el origen que contiene los objetos en cualquier punto de sus
+// órbitas. Para los objetos sin órbita se usa su posición actual.
+fn scene_radius(objects: &[RenderObject]) -> f32 {
+    objects
+        .iter()
+        .map(|obj| {
+            let offset = obj.orbit.map_or(obj.position.magnitude(), |orbit| orbit.apoapsis());
+            // Los objetos rotan alrededor de su origen, así que se mide desde ahí. La esfera envolvente y la
+            // esquina más lejana de la caja envolvente acotan la malla en cualquier giro; se usa la menor. La
+            // esfera es más ajustada en los planetas y la caja en las mallas que no se centraron, como los
+            // terrenos de `heightmap:`.
+            let (center, radius) = obj.mesh.bounding_sphere();
+            let (min, max) = obj.mesh.bounding_box();
+            let own = (center.magnitude() + radius).min(min.abs().sup(&max.abs()).magnitude()) * obj.scale;
+            offset + own.max(scene_radius(&obj.children))
+        })
+        .fold(0.0, f32::max)
+}
+
+// Dibuja en `framebuffer` la órbita de cada objeto que tiene una, como un polígono de `samples` lados alrededor de
+// su padre. Las líneas solo ocupan el fondo, así que quedan detrás de los cuerpos; los segmentos con un extremo
+// detrás de la cámara se omiten. Un punto encima de todo marca dónde va cada objeto sobre su órbita, para
+// encontrar las lunas que se ven más pequeñas que un píxel.
+fn draw_orbit_trails(
+    framebuffer: &mut Framebuffer,
+    objects: &[RenderObject],
+    parent: &Mat4,
+    view_projection: &Mat4,
+    samples: usize,
+) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let to_screen = |point: Vec3| {
+        let clip = view_projection * parent * point.push(1.0);
+        (clip.w > 1e-3).then(|| {
+            let ndc = clip.xyz() / clip.w;
+            (((ndc.x + 1.0) * 0.5 * width) as i32, ((1.0 - ndc.y) * 0.5 * height) as i32)
+        })
+    };
+
+    for obj in objects {
+        if let Some(orbit) = &obj.orbit {
+            let points: Vec<_> = (0..samples)
+                .map(|i| to_screen(orbit_point(orbit, i as f32 / samples as f32 * std::f32::consts::TAU)))
+                .collect();
+            for i in 0..samples {
+                if let (Some((x0, y0)), Some((x1, y1))) = (points[i], points[(i + 1) % samples]) {
+                    framebuffer.draw_background_line(x0, y0, x1, y1, Color::new(60, 70, 100));
+                }
+            }
+            if let Some((x, y)) = to_screen(obj.position) {
+                framebuffer.fill_circle(x, y, 2, Color::new(140, 160, 220));
+            }
+        }
+        draw_orbit_trails(framebuffer, &obj.children, &obj.get_frame_matrix(parent), view_projection, samples);
+    }
+}
+
+// Devuelve la distancia de la cámara orbital a la que la esfera que contiene la escena cabe a lo ancho de una
+// imagen con relación de aspecto `aspect`. Las órbitas son casi horizontales, así que no hace falta que quepa
+// también a lo alto. Con proyección ortográfica no depende de la distancia y devuelve cero.
+fn framing_distance(objects: &[RenderObject], projection: &Projection, fov_y: f32, aspect: f32) -> f32 {
+    if !projection.is_perspective() {
+        return 0.0;
+    }
+    let half_fov_x = ((fov_y * 0.5).tan() * aspect.max(1.0)).atan();
+    scene_radius(objects) / half_fov_x.sin()
+}
+
+// Guarda en `dir` la malla de cada objeto de la escena y de sus hijos, en el orden en que se dibujan.
+fn dump_meshes(objects: &[RenderObject], dir: &str) -> Result<(), String> {
+    fn collect<'a>(objects: &'a [RenderObject], out: &mut Vec<&'a ObjMesh>) {
+        for obj in objects {
+            out.push(&obj.mesh);
+            collect(&obj.children, out);
+        }
+    }
+
+    let mut meshes = Vec::new();
+    collect(objects, &mut meshes);
+    std::fs::create_dir_all(dir).map_err(|e| format!("Error creating {}: {}", dir, e))?;
+    for (i, mesh) in meshes.iter().enumerate() {
+        let path = Path::new(dir).join(format!("mesh_{:02}.obj", i));
+        let path = path.to_string_lossy();
+        mesh.save_to_obj(&path)?;
+        println!("Malla {}/{} guardada en {} ({} triángulos)", i + 1, meshes.len(), path, mesh.indices.len() / 3);
+    }
+    Ok(())
+}
+
+// Objeto del grafo de escena listo para dibujar, con sus matrices de modelo y de cuerpo en el mundo.
+type FlatNode<'a> = (&'a RenderObject, Mat4, Mat4);
+
+// Recorre el grafo de escena en profundidad, padres antes que hijos, y agrega a `out` cada objeto con sus
+// matrices en el mundo.
+fn flatten_scene<'a>(objects: &'a [RenderObject], parent: &Mat4, time: f32, out: &mut Vec<FlatNode<'a>>) {
+    for obj in objects {
+        out.push((obj, obj.get_model_matrix(parent, time), obj.get_body_matrix(parent)));
+        flatten_scene(&obj.children, &obj.get_frame_matrix(parent), time, out);
+    }
+}
+
+// Dibuja los objetos de una escena en `framebuffer` vista desde `camera` y devuelve las estadísticas sumadas.
+// No usa raylib, así que sirve igual para la ventana y para el modo sin ventana.
+fn render_scene(
+    renderer: &mut Renderer,
+    framebuffer: &mut Framebuffer,
+    objects: &[RenderObject],
+    camera: &Camera,
+    projection: &Projection,
+    time: f32,
+    cull_backfaces: bool,
+) -> RenderStats {
+    renderer.camera_position = camera.eye();
+    let view_matrix = camera.view_matrix();
+
+    // La vista de profundidad usa solo la franja que ocupa la escena; con los planos de la proyección
+    // (0.1 a 100) todos los objetos quedarían casi del mismo gris.
+    let (near, far) = projection.depth_range();
+    let eye_distance = renderer.camera_position.magnitude();
+    let radius = scene_radius(objects);
+    renderer.depth_range = ((eye_distance - radius).max(near), (eye_distance + radius).min(far));
+    let projection_matrix = projection.matrix(framebuffer.width as f32 / framebuffer.height.max(1) as f32, camera.fov_y);
+
+    let [top, bottom] = renderer.background;
+    framebuffer.clear_gradient(top, bottom);
+    renderer.clear_depth();
+
+    let mut nodes = Vec::new();
+    flatten_scene(objects, &Mat4::identity(), time, &mut nodes);
+
+    // Los cuerpos en órbita, como las lunas, eclipsan a los demás. Los que están fijos no, porque en el centro
+    // suele ir la estrella y la luz direccional no sale de ella. Las mallas tienen radio 1, así que el radio de
+    // la esfera es la escala del objeto.
+    renderer.occluders = nodes
+        .iter()
+        .filter(|(obj, _, _)| obj.opaque && obj.orbit.is_some())
+        .map(|(obj, model, _)| SphereOccluder { center: model.column(3).xyz(), radius: obj.scale })
+        .collect();
+
+    // Los anillos son planos en el XZ de su marco del cuerpo, que ya incluye la inclinación del eje; el giro
+    // alrededor de la normal no mueve el plano.
+    let [inner, outer] = RING_RADII;
+    renderer.rings = nodes
+        .iter()
+        .filter(|(obj, _, _)| obj.shader.is_ring())
+        .map(|(obj, _, body)| RingOccluder {
+            center: body.column(3).xyz(),
+            normal: body.column(1).xyz().normalize(),
+            inner_radius: inner * obj.scale,
+            outer_radius: outer * obj.scale,
+        })
+        .collect();
+
+    // El mapa de sombras de la primera luz cubre la esfera que contiene la escena y solo guarda los objetos
+    // opacos; los translúcidos reciben sombra pero no la proyectan.
+    if let (Some(map), Some(light)) = (renderer.shadow_map.as_mut(), renderer.lights.first()) {
+        map.begin(&light.dir, Vec3::zeros(), radius);
+        for (obj, model_matrix, _) in nodes.iter().filter(|(obj, _, _)| obj.opaque) {
+            map.render_mesh(&obj.mesh, model_matrix);
+        }
+    }
+
+    // Los objetos opacos se dibujan primero, en el orden del grafo. Los translúcidos van después, del más lejano
+    // al más cercano a la cámara, para que cada uno se mezcle sobre lo que queda detrás de él; como no escriben
+    // profundidad, tampoco tapan a los translúcidos que se dibujan más tarde.
+    let (opaque, mut translucent): (Vec<_>, Vec<_>) = nodes.into_iter().partition(|(obj, _, _)| obj.opaque);
+    let eye = renderer.camera_position;
+    let distance = |model: &Mat4| (model.column(3).xyz() - eye).magnitude();
+    translucent.sort_by(|(_, a, _), (_, b, _)| distance(b).total_cmp(&distance(a)));
+
+    let mut stats = RenderStats::default();
+    for (obj, model_matrix, body_matrix) in opaque.into_iter().chain(translucent) {
+        renderer.set_body_frame(&body_matrix);
+        renderer.depth_write = obj.opaque;
+        renderer.blend_mode = if obj.opaque { BlendMode::Alpha } else { obj.blend };
+        renderer.cull_backfaces = cull_backfaces && !obj.double_sided;
+        // Invertir la cara frontal hace que el culling descarte las caras delanteras en su lugar.
+        renderer.front_face = if obj.back_faces_only { Winding::Cw } else { Winding::Ccw };
+
+        let draw = DrawCall {
+            mesh: &obj.mesh,
+            shader: obj.shader.as_ref(),
+            material: obj.material.unwrap_or_else(|| obj.shader.default_material()),
+            model_matrix,
+            time,
+        };
+        stats += renderer.render_mesh(framebuffer, &draw, &view_matrix, &projection_matrix);
+    }
+
+    // En la vista de sobredibujo los colores se reemplazan por el conteo de escrituras de cada píxel.
+    if renderer.debug_view == DebugView::Overdraw {
+        framebuffer.overdraw_heatmap();
+    }
+    stats
+}
+
 // La función principal que se ejecuta al iniciar el programa.
 fn main() {
+    let args = Args::parse();
     println!("Iniciando aplicación...");
 
-    let (mut rl, thread) = raylib::init()
-        .size(WIDTH as i32, HEIGHT as i32)
-        .title("Planetas con Luna y Anillos - Software Renderer")
-        .build();
-
-    rl.set_target_fps(60);
-
     println!("Generando geometría...");
     let sphere_mesh = ObjMesh::create_sphere(1.0, 50, 50);
     
     // Intenta cargar el modelo .obj, si falla usa la esfera procedural
     let obj_sphere = match ObjMesh::load_from_obj("assets/sphere.obj") {
-        Ok(mesh) => {
+        Ok(mut mesh) => {
             println!("✓ sphere.obj cargado exitosamente");
+            if let Some(material) = &mesh.material {
+                println!("  Material: {}", material.name);
+            }
+            // Sin vértices repetidos, centrado y a radio 1 para que rote en su lugar y se vea igual que la esfera
+            // procedural.
+            mesh.weld_vertices(1e-5);
+            mesh.center();
+            mesh.normalize_scale(1.0);
             Some(mesh)
         }
         Err(e) => {
@@ -88,107 +453,561 @@ fn main() {
         }
     };
     
-    let ring_mesh = ObjMesh::create_ring(1.3, 2.0, 100);
+    let ring_mesh = ObjMesh::create_ring(RING_RADII[0], RING_RADII[1], 100);
+
+    // Intenta cargar las escenas desde scene.toml; se validan una vez construyéndolas al inicio
+    let scene_file = match scene::load_scene_file(SCENE_PATH) {
+        Ok(Some(file)) => match file.build(&sphere_mesh, &ring_mesh) {
+            Ok(_) => {
+                println!("✓ {} cargado con {} escenas", SCENE_PATH, file.scenes.len());
+                Some(file)
+            }
+            Err(e) => {
+                println!("⚠ Error en {}: {}", SCENE_PATH, e);
+                println!("  Usando escenas integradas");
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(e) => {
+            println!("⚠ {}", e);
+            println!("  Usando escenas integradas");
+            None
+        }
+    };
 
     // Variable para controlar qué malla usar
     let mut use_obj_model = false;
 
     // Función helper para obtener la malla actual
     let get_sphere = |use_obj: bool| -> ObjMesh {
-        if use_obj && obj_sphere.is_some() {
-            obj_sphere.as_ref().unwrap().clone()
-        } else {
-            sphere_mesh.clone()
+        match obj_sphere.as_ref() {
+            Some(mesh) if use_obj => mesh.clone(),
+            _ => sphere_mesh.clone(),
         }
     };
 
+    // Capa de atmósfera: una esfera un poco más grande de la que solo se ven las caras traseras,
+    // así el halo aparece alrededor del planeta sin taparlo.
+    let atmosphere_shell = |sphere: &ObjMesh| RenderObject {
+        mesh: sphere.clone(),
+        shader: Box::new(AtmosphereShader),
+        position: Vec3::new(0.0, 0.0, 0.0),
+        scale: 1.08,
+        rotation_speed: 0.0,
+        rotation_axis: Vec3::new(0.0, 1.0, 0.0),
+        axial_tilt: 0.0,
+        double_sided: false,
+        back_faces_only: true,
+        opaque: false,
+        blend: BlendMode::Alpha,
+        material: None,
+        orbit: None,
+        children: Vec::new(),
+    };
+
+    // Sistema solar: una estrella en el centro con planetas a distintos radios y velocidades, algunos con lunas,
+    // nubes o anillos como hijos. Las fases separan a los planetas para que no empiecen alineados.
+    let solar_system = |sphere: &ObjMesh| -> Vec<RenderObject> {
+        let body = |shader: Box<dyn PlanetShader>, scale: f32, rotation_speed: f32| RenderObject {
+            rotation_speed,
+            ..RenderObject::new(sphere.clone(), shader, Vec3::new(0.0, 0.0, 0.0), scale)
+        };
+        let orbit = |radius: f32, speed: f32, inclination_degrees: f32, phase_degrees: f32| {
+            Some(Orbit::circular(radius, speed, inclination_degrees.to_radians(), phase_degrees.to_radians()))
+        };
+        let moon = |scale: f32, orbit: Option<Orbit>| RenderObject {
+            orbit,
+            ..body(Box::<MoonShader>::default(), scale, 0.5)
+        };
+
+        vec![RenderObject {
+            children: vec![
+                RenderObject {
+                    orbit: orbit(2.6, 0.8, 2.0, 0.0),
+                    ..body(Box::<RockyPlanet>::default(), 0.25, 1.0)
+                },
+                RenderObject {
+                    orbit: orbit(3.8, 0.55, 1.0, 120.0),
+                    ..body(Box::<DesertPlanet>::default(), 0.3, 0.8)
+                },
+                RenderObject {
+                    orbit: orbit(5.3, 0.4, 0.0, 220.0),
+                    axial_tilt: 23.4_f32.to_radians(),
+                    children: vec![
+                        // Las nubes no tienen órbita, así que se quedan centradas en la Tierra; la inclinación
+                        // no se hereda y se repite para que giren sobre el mismo eje.
+                        RenderObject {
+                            axial_tilt: 23.4_f32.to_radians(),
+                            opaque: false,
+                            ..body(Box::<CloudShader>::default(), 0.41, 1.3)
+                        },
+                        moon(0.1, orbit(0.75, 2.0, 10.0, 0.0)),
+                    ],
+                    ..body(Box::<EarthPlanet>::default(), 0.4, 1.0)
+                },
+                RenderObject {
+                    orbit: orbit(7.4, 0.25, 3.0, 300.0),
+                    axial_tilt: 25.0_f32.to_radians(),
+                    children: vec![RenderObject {
+                        mesh: ring_mesh.clone(),
+                        axial_tilt: 25.0_f32.to_radians(),
+                        double_sided: true,
+                        opaque: false,
+                        ..body(Box::new(RingShader), 0.55, 0.3)
+                    }],
+                    ..body(Box::<GasGiant>::default(), 0.6, 1.0)
+                },
+                RenderObject {
+                    orbit: orbit(9.4, 0.15, 5.0, 40.0),
+                    children: vec![
+                        moon(0.1, orbit(0.8, 1.2, 15.0, 90.0)),
+                        moon(0.07, orbit(1.05, 0.9, -8.0, 250.0)),
+                    ],
+                    ..body(Box::<IcePlanet>::default(), 0.45, 0.7)
+                },
+            ],
+            ..body(Box::new(StarShader), 1.2, 0.2)
+        }]
+    };
+
     // Función para crear todas las escenas
     let create_scenes = |use_obj: bool| -> Vec<Vec<RenderObject>> {
         let current_sphere = get_sphere(use_obj);
+
+        if let Some(file) = &scene_file {
+            match file.build(&current_sphere, &ring_mesh) {
+                Ok(scenes) => return scenes,
+                Err(e) => println!("⚠ Error en {}: {}, usando escenas integradas", SCENE_PATH, e),
+            }
+        }
         
         vec![
-            // Escena 0: Planeta Rocoso
-            vec![RenderObject::new(
-                current_sphere.clone(),
-                Box::new(RockyPlanet),
-                Vec3::new(0.0, 0.0, 0.0),
-                1.0,
-            )],
-            
-            // Escena 1: Gigante Gaseoso + Anillos
+            // Escena 0: Planeta Rocoso + Atmósfera
             vec![
                 RenderObject::new(
                     current_sphere.clone(),
-                    Box::new(GasGiant),
+                    Box::new(RockyPlanet::default()),
                     Vec3::new(0.0, 0.0, 0.0),
-                    1.2,
+                    1.0,
                 ),
+                atmosphere_shell(&current_sphere),
+            ],
+            
+            // Escena 1: Gigante Gaseoso + Anillos, con el eje inclinado 25° como Saturno; el anillo comparte
+            // la inclinación para quedar en el plano del ecuador.
+            vec![
+                RenderObject {
+                    axial_tilt: 25.0_f32.to_radians(),
+                    ..RenderObject::new(
+                        current_sphere.clone(),
+                        Box::new(GasGiant::default()),
+                        Vec3::new(0.0, 0.0, 0.0),
+                        1.2,
+                    )
+                },
                 RenderObject {
                     mesh: ring_mesh.clone(),
                     shader: Box::new(RingShader),
                     position: Vec3::new(0.0, 0.0, 0.0),
                     scale: 1.0,
                     rotation_speed: 0.3,
-                    rotation_axis: Vec3::new(0.3, 1.0, 0.1).normalize(),
+                    rotation_axis: Vec3::new(0.0, 1.0, 0.0),
+                    axial_tilt: 25.0_f32.to_radians(),
+                    double_sided: true, // El anillo es plano y se ve desde ambos lados.
+                    back_faces_only: false,
+                    opaque: false,
+                    blend: BlendMode::Alpha,
+                    material: None,
+                    orbit: None,
+                    children: Vec::new(),
                 },
             ],
             
             // Escena 2: Planeta Cristalino
             vec![RenderObject::new(
                 current_sphere.clone(),
-                Box::new(CrystalPlanet),
+                Box::new(CrystalPlanet::default()),
                 Vec3::new(0.0, 0.0, 0.0),
                 1.0,
             )],
             
-            // Escena 3: Planeta de Lava + Luna
-            vec![
-                RenderObject::new(
-                    current_sphere.clone(),
-                    Box::new(LavaPlanet),
-                    Vec3::new(0.0, 0.0, 0.0),
-                    1.0,
-                ),
-                RenderObject {
+            // Escena 3: Planeta de Lava + Luna (la luna es hija del planeta y orbita a su alrededor)
+            vec![RenderObject {
+                children: vec![RenderObject {
                     mesh: current_sphere.clone(),
-                    shader: Box::new(MoonShader),
+                    shader: Box::new(MoonShader::default()),
                     position: Vec3::new(0.0, 0.0, 0.0),
                     scale: 0.3,
                     rotation_speed: 0.5,
                     rotation_axis: Vec3::new(0.0, 1.0, 0.0),
-                },
-            ],
+                    axial_tilt: 0.0,
+                    double_sided: false,
+                    back_faces_only: false,
+                    opaque: true,
+                    blend: BlendMode::Alpha,
+                    material: None,
+                    // Órbita excéntrica e inclinada: pasa a 1.6 unidades del planeta y se aleja hasta 3.6.
+                    orbit: Some(Orbit {
+                        semi_major: 2.6,
+                        semi_minor: 2.4,
+                        speed: 0.5,
+                        inclination: 12.0_f32.to_radians(),
+                        phase: 0.0,
+                    }),
+                    children: Vec::new(),
+                }],
+                ..RenderObject::new(
+                    current_sphere.clone(),
+                    Box::new(LavaPlanet::default()),
+                    Vec3::new(0.0, 0.0, 0.0),
+                    1.0,
+                )
+            }],
             
             // Escena 4: Mundo Congelado + Luna
+            vec![RenderObject {
+                children: vec![RenderObject {
+                    mesh: current_sphere.clone(),
+                    shader: Box::new(MoonShader::default()),
+                    position: Vec3::new(0.0, 0.0, 0.0),
+                    scale: 0.25,
+                    rotation_speed: 0.3,
+                    rotation_axis: Vec3::new(0.0, 1.0, 0.0),
+                    axial_tilt: 0.0,
+                    double_sided: false,
+                    back_faces_only: false,
+                    opaque: true,
+                    blend: BlendMode::Alpha,
+                    material: None,
+                    orbit: Some(Orbit::circular(2.5, 0.5, 7.0_f32.to_radians(), 0.0)), // Sube y baja unas 0.3 unidades.
+                    children: Vec::new(),
+                }],
+                ..RenderObject::new(
+                    current_sphere.clone(),
+                    Box::new(IcePlanet::default()),
+                    Vec3::new(0.0, 0.0, 0.0),
+                    1.0,
+                )
+            }],
+
+            // Escena 5: Planeta Tierra + Nubes
             vec![
                 RenderObject::new(
                     current_sphere.clone(),
-                    Box::new(IcePlanet),
+                    Box::new(EarthPlanet::default()),
                     Vec3::new(0.0, 0.0, 0.0),
                     1.0,
                 ),
+                // Capa de nubes un poco más grande y que gira más rápido que la superficie.
                 RenderObject {
                     mesh: current_sphere.clone(),
-                    shader: Box::new(MoonShader),
+                    shader: Box::new(CloudShader::default()),
                     position: Vec3::new(0.0, 0.0, 0.0),
-                    scale: 0.25,
-                    rotation_speed: 0.3,
+                    scale: 1.02,
+                    rotation_speed: 1.3,
                     rotation_axis: Vec3::new(0.0, 1.0, 0.0),
+                    axial_tilt: 0.0,
+                    double_sided: false,
+                    back_faces_only: false,
+                    opaque: false, // Las nubes se mezclan con la superficie.
+                    blend: BlendMode::Alpha,
+                    material: None,
+                    orbit: None,
+                    children: Vec::new(),
                 },
+                atmosphere_shell(&current_sphere),
             ],
+
+            // Escena 6: Estrella
+            vec![RenderObject::new(
+                current_sphere.clone(),
+                Box::new(StarShader),
+                Vec3::new(0.0, 0.0, 0.0),
+                1.0,
+            )],
+
+            // Escena 7: Planeta Desértico
+            vec![RenderObject::new(
+                current_sphere.clone(),
+                Box::new(DesertPlanet::default()),
+                Vec3::new(0.0, 0.0, 0.0),
+                1.0,
+            )],
+
+            // Escena 8: Planeta Oceánico
+            vec![RenderObject::new(
+                current_sphere.clone(),
+                Box::new(OceanPlanet),
+                Vec3::new(0.0, 0.0, 0.0),
+                1.0,
+            )],
+
+            // Escena 9: Sistema Solar
+            solar_system(&current_sphere),
         ]
     };
 
+    // Crea las escenas con las mallas facetadas si está activo el sombreado plano (tecla U).
+    let mut flat_shading = false;
+    let build_scenes = |use_obj: bool, flat: bool| -> Vec<Vec<RenderObject>> {
+        let mut scenes = create_scenes(use_obj);
+        if flat {
+            scenes.iter_mut().for_each(|scene| facet_meshes(scene));
+        }
+        scenes
+    };
+
     // Crea las escenas iniciales
-    let mut scenes = create_scenes(use_obj_model);
+    let mut scenes = build_scenes(use_obj_model, flat_shading);
+    if args.scene > scenes.len() {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ValueValidation,
+                format!("scene {} does not exist; there are {} scenes", args.scene, scenes.len()),
+            )
+            .exit();
+    }
+
+    let mut renderer = Renderer::new(args.width, args.height, SUPERSAMPLE);
+    renderer.parallel = !args.serial;
+    if args.shadow_map_size > 0 {
+        renderer.shadow_map = Some(ShadowMap::new(args.shadow_map_size));
+    }
+
+    // Las texturas del archivo de escenas quedan en el renderizador; si alguna falla, los objetos que la
+    // usan se dibujan grises.
+    if let Some(file) = &scene_file {
+        let (textures, errors) = file.load_textures();
+        for e in &errors {
+            println!("⚠ {}", e);
+        }
+        if textures.len() > errors.len() {
+            println!("✓ {} texturas cargadas", textures.len() - errors.len());
+        }
+        renderer.textures = textures;
+    }
+    let (internal_width, internal_height) = renderer.internal_size();
+    // El renderizador dibuja en `framebuffer`; `display` tiene el tamaño de la ventana y es lo que se muestra.
+    let mut framebuffer = Framebuffer::new(internal_width, internal_height);
+    let mut display = Framebuffer::new(args.width, args.height);
+
+    let shader_names: Vec<String> = match &scene_file {
+        Some(file) => file
+            .names()
+            .iter()
+            .enumerate()
+            .map(|(i, name)| format!("{}: {}", i + 1, name))
+            .collect(),
+        None => vec![
+            "1: Planeta Rocoso + Atmósfera".to_string(),
+            "2: Gigante Gaseoso + Anillos".to_string(),
+            "3: Planeta Cristalino".to_string(),
+            "4: Planeta de Lava + Luna".to_string(),
+            "5: Mundo Congelado + Luna".to_string(),
+            "6: Planeta Tierra + Nubes".to_string(),
+            "7: Estrella".to_string(),
+            "8: Planeta Desértico".to_string(),
+            "9: Planeta Oceánico".to_string(),
+            "10: Sistema Solar".to_string(),
+        ],
+    };
+
+    // Ajustes de bloom por escena. Los planetas de lava y cristal usan un umbral más bajo para que brillen más,
+    // y la estrella uno todavía más bajo y más intenso para que su resplandor se extienda.
+    let bloom_settings: Vec<BloomSettings> = match &scene_file {
+        Some(file) => file.bloom_settings(),
+        None => {
+            let glowing = BloomSettings { threshold: 0.6, ..BloomSettings::default() };
+            vec![
+                BloomSettings::default(),
+                BloomSettings::default(),
+                glowing,
+                glowing,
+                BloomSettings::default(),
+                BloomSettings::default(),
+                BloomSettings { threshold: 0.5, blur_radius: 12, intensity: 0.9 },
+                BloomSettings::default(),
+                BloomSettings::default(),
+                glowing,
+            ]
+        }
+    };
+
+    // Fondo de cada escena; las integradas comparten el mismo.
+    let backgrounds: Vec<[Color; 2]> = match &scene_file {
+        Some(file) => file.backgrounds(),
+        None => Vec::new(),
+    };
+    let background = |scene: usize| backgrounds.get(scene).copied().unwrap_or(scene::DEFAULT_BACKGROUND);
+
+    // Teclas numéricas para seleccionar escenas, en orden.
+    let scene_keys = [
+        KeyboardKey::KEY_ONE,
+        KeyboardKey::KEY_TWO,
+        KeyboardKey::KEY_THREE,
+        KeyboardKey::KEY_FOUR,
+        KeyboardKey::KEY_FIVE,
+        KeyboardKey::KEY_SIX,
+        KeyboardKey::KEY_SEVEN,
+        KeyboardKey::KEY_EIGHT,
+        KeyboardKey::KEY_NINE,
+        KeyboardKey::KEY_ZERO,
+    ];
+
+    let mut current_scene = args.scene - 1;
+    let mut clock = SimClock::new();
+    let mut cull_backfaces = true;
+    let mut rotate_light = false;
+    let mut show_stars = true;
+    let mut show_orbits = false;
+    let mut bloom_enabled = false;
+    let mut gamma_correct = false; // Desactivada por defecto para conservar el aspecto original.
+    let starfield = Starfield::new(0.0015, 1337);
+    let base_light_dir = renderer.lights[0].dir;
+
+    // Luz de relleno tenue y azulada que ilumina el lado nocturno de los planetas.
+    let fill_light = DirectionalLight {
+        dir: Vec3::new(-1.0, -0.3, -0.5).normalize(),
+        color: Vec3::new(0.4, 0.5, 0.8),
+        intensity: 0.4,
+    };
+    let fov_y = 60.0_f32.to_radians();
+    let mut projection = Projection::Perspective {
+        near: 0.1,
+        far: 100.0,
+    };
+    // La distancia inicial se aleja lo necesario para que quepa la escena, con las mallas que use.
+    let mut camera = Camera::new(3.5, fov_y);
+    let aspect = args.width as f32 / args.height as f32;
+    camera.ensure_distance(framing_distance(&scenes[current_scene], &projection, camera.fov_y, aspect));
+    let orbit_sensitivity = 0.01; // Radianes por píxel de arrastre del ratón.
+
+    // Modo sin ventana: dibuja la escena pedida en uno o varios tiempos, guarda cada cuadro y termina
+    // sin inicializar raylib.
+    if let Some(dir) = &args.dump_obj {
+        if let Err(e) = dump_meshes(&scenes[current_scene], dir) {
+            eprintln!("⚠ {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.headless {
+        let name = shader_names.get(current_scene).map_or("", |name| name.as_str());
+
+        // Con `--bench` se mide cada camino del renderizador dibujando los mismos cuadros, separados como a
+        // `fps` cuadros por segundo. Un cuadro previo sin medir crea los hilos y calienta las cachés.
+        if let Some(frames) = args.bench {
+            renderer.background = background(current_scene);
+            let tile_rows = renderer.tile_rows;
+            let mut average_ms = |parallel: bool, tile_rows: usize| {
+                renderer.parallel = parallel;
+                renderer.tile_rows = tile_rows;
+                let mut draw = |time: f32| {
+                    update_orbits(&mut scenes[current_scene], time);
+                    render_scene(
+                        &mut renderer,
+                        &mut framebuffer,
+                        &scenes[current_scene],
+                        &camera,
+                        &projection,
+                        time,
+                        cull_backfaces,
+                    );
+                };
+                draw(args.time);
+                let start = Instant::now();
+                for i in 0..frames {
+                    draw(args.time + i as f32 / args.fps as f32);
+                }
+                start.elapsed().as_secs_f64() * 1000.0 / frames as f64
+            };
+
+            // Con una franja de la altura del búfer, la única franja recorre todos los triángulos de la escena.
+            let parallel_ms = average_ms(true, tile_rows);
+            let serial_ms = average_ms(false, tile_rows);
+            let single_band_ms = average_ms(false, internal_height);
+            println!("{}: {} cuadros de {}x{}", name, frames, args.width, args.height);
+            println!("  En paralelo ({} hilos): {:.2} ms por cuadro", rayon::current_num_threads(), parallel_ms);
+            println!("  En un hilo: {:.2} ms por cuadro", serial_ms);
+            println!("  En un hilo sin franjas: {:.2} ms por cuadro", single_band_ms);
+            println!("  Aceleración en paralelo: {:.2}x", serial_ms / parallel_ms);
+            println!("  Aceleración de las franjas: {:.2}x", single_band_ms / serial_ms);
+            return;
+        }
+
+        let mut render_to_file = |time: f32, path: &str| -> Result<RenderStats, String> {
+            update_orbits(&mut scenes[current_scene], time);
+            renderer.background = background(current_scene);
+            let start = Instant::now();
+            let stats = render_scene(
+                &mut renderer,
+                &mut framebuffer,
+                &scenes[current_scene],
+                &camera,
+                &projection,
+                time,
+                cull_backfaces,
+            );
+            framebuffer.downsample_into(&mut display, renderer.supersample);
+            if show_stars {
+                starfield.draw(&mut display, time);
+            }
+            // Sin raylib no hay texto en pantalla; la etiqueta se escribe con la fuente del búfer.
+            if args.label {
+                let elapsed_ms = start.elapsed().as_secs_f32() * 1000.0;
+                let label = format!("{}\nt = {:.2} s - {:.1} ms", name, time, elapsed_ms);
+                display.draw_text(10, 10, &label, Color::WHITE, 2);
+            }
+            if path.ends_with(".ppm") {
+                display.save_ppm(path)?;
+            } else {
+                display.save_png(path)?;
+            }
+            Ok(stats)
+        };
 
-    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
-    let renderer = Renderer::new(WIDTH, HEIGHT);
+        let result = match args.frames {
+            None => render_to_file(args.time, &args.out).map(|stats| {
+                println!("✓ {} guardada en {} ({} triángulos dibujados)", name, args.out, stats.drawn);
+            }),
+            // El paso divide la duración en partes iguales sin repetir el final, así una vuelta completa
+            // forma un ciclo sin cuadros duplicados al unirla en un GIF.
+            Some(frames) => std::fs::create_dir_all(&args.out_dir)
+                .map_err(|e| format!("Error creating {}: {}", args.out_dir, e))
+                .and_then(|()| {
+                    let step = args.duration / frames as f32;
+                    for i in 0..frames {
+                        let path = Path::new(&args.out_dir).join(format!("frame_{:04}.png", i));
+                        let path = path.to_string_lossy();
+                        render_to_file(args.time + i as f32 * step, &path)?;
+                        println!("Cuadro {}/{} guardado en {}", i + 1, frames, path);
+                    }
+                    println!("✓ {}: {} cuadros guardados en {}", name, frames, args.out_dir);
+                    Ok(())
+                }),
+        };
+
+        if let Err(e) = result {
+            eprintln!("⚠ {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let (mut rl, thread) = raylib::init()
+        .size(args.width as i32, args.height as i32)
+        .title("Planetas con Luna y Anillos - Software Renderer")
+        .resizable()
+        .build();
+
+    rl.set_target_fps(args.fps);
 
     println!("Creando textura...");
     let initial_image = Image::gen_image_color(
-        WIDTH as i32,
-        HEIGHT as i32,
+        args.width as i32,
+        args.height as i32,
         raylib::color::Color::BLACK,
     );
 
@@ -196,105 +1015,261 @@ fn main() {
         .load_texture_from_image(&thread, &initial_image)
         .expect("No se pudo crear textura");
 
-    let shader_names = vec![
-        "1: Planeta Rocoso",
-        "2: Gigante Gaseoso + Anillos",
-        "3: Planeta Cristalino",
-        "4: Planeta de Lava + Luna",
-        "5: Mundo Congelado + Luna",
-    ];
-
-    let mut current_scene = 0;
-    let mut paused = false;
-    let mut paused_time = 0.0f32;
-    let mut last_active_time = 0.0f32;
-
     println!("Entrando al loop principal...");
 
     while !rl.window_should_close() {
-        let current_real_time = rl.get_time() as f32;
-        
-        let time = if paused {
-            paused_time
-        } else {
-            last_active_time + (current_real_time - last_active_time)
-        };
+        // Todo el cuadro usa el mismo tiempo de simulación, que no avanza mientras está en pausa.
+        clock.tick(rl.get_time() as f32);
+        let time = clock.sim_time();
+
+        // Si la ventana cambió de tamaño, los búferes, el renderizador y la textura siguen a la nueva resolución.
+        if rl.is_window_resized() {
+            let width = rl.get_screen_width().max(1) as usize;
+            let height = rl.get_screen_height().max(1) as usize;
+
+            renderer.resize(width, height);
+            let (internal_width, internal_height) = renderer.internal_size();
+            framebuffer.resize(internal_width, internal_height);
+            display.resize(width, height);
+
+            let image = Image::gen_image_color(width as i32, height as i32, raylib::color::Color::BLACK);
+            match rl.load_texture_from_image(&thread, &image) {
+                Ok(new_texture) => texture = new_texture,
+                Err(e) => eprintln!("Error recreando textura: {:?}", e),
+            }
+        }
 
         // Cambio de escena
-        if rl.is_key_pressed(KeyboardKey::KEY_ONE) { current_scene = 0; }
-        if rl.is_key_pressed(KeyboardKey::KEY_TWO) { current_scene = 1; }
-        if rl.is_key_pressed(KeyboardKey::KEY_THREE) { current_scene = 2; }
-        if rl.is_key_pressed(KeyboardKey::KEY_FOUR) { current_scene = 3; }
-        if rl.is_key_pressed(KeyboardKey::KEY_FIVE) { current_scene = 4; }
+        for (i, key) in scene_keys.iter().enumerate() {
+            if i < scenes.len() && rl.is_key_pressed(*key) {
+                current_scene = i;
+                let aspect = display.width as f32 / display.height.max(1) as f32;
+                camera.ensure_distance(framing_distance(&scenes[current_scene], &projection, camera.fov_y, aspect));
+            }
+        }
         
         // Toggle entre esfera procedural y .obj con la tecla M
         if rl.is_key_pressed(KeyboardKey::KEY_M) && obj_sphere.is_some() {
             use_obj_model = !use_obj_model;
-            scenes = create_scenes(use_obj_model);
+            scenes = build_scenes(use_obj_model, flat_shading);
+            current_scene = current_scene.min(scenes.len() - 1);
             println!("Cambiando a: {}", 
                 if use_obj_model { "sphere.obj" } else { "Esfera Procedural" });
         }
         
-        // Pausa
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-            if paused {
-                let pause_duration = current_real_time - paused_time;
-                last_active_time = current_real_time - pause_duration;
-                paused = false;
+        // Alterna entre normales suaves y facetadas con la tecla U; las escenas se reconstruyen desde las mallas
+        // originales para poder volver al sombreado suave.
+        if rl.is_key_pressed(KeyboardKey::KEY_U) {
+            flat_shading = !flat_shading;
+            scenes = build_scenes(use_obj_model, flat_shading);
+            println!("Sombreado facetado: {}", if flat_shading { "activado" } else { "desactivado" });
+        }
+
+        // Activa o desactiva el backface culling con la tecla C
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            cull_backfaces = !cull_backfaces;
+            println!("Backface culling: {}", if cull_backfaces { "activado" } else { "desactivado" });
+        }
+
+        // Cambia entre relleno, wireframe y puntos con la tecla R
+        if rl.is_key_pressed(KeyboardKey::KEY_R) {
+            renderer.render_mode = renderer.render_mode.next();
+            println!("Modo de dibujo: {:?}", renderer.render_mode);
+        }
+
+        // Alterna entre sombreado por vértice (Gouraud) y por píxel (Phong) con la tecla V
+        if rl.is_key_pressed(KeyboardKey::KEY_V) {
+            renderer.shading_model = renderer.shading_model.toggled();
+            println!("Sombreado: {:?}", renderer.shading_model);
+        }
+
+        // Recorre las vistas de depuración con la tecla X
+        if rl.is_key_pressed(KeyboardKey::KEY_X) {
+            renderer.debug_view = renderer.debug_view.next();
+            println!("Vista de depuración: {:?}", renderer.debug_view);
+        }
+
+        // Recorre las funciones de la prueba de profundidad con la tecla Z; `Greater` y `Always` muestran en qué
+        // orden se dibujan las caras.
+        if rl.is_key_pressed(KeyboardKey::KEY_Z) {
+            renderer.depth_func = renderer.depth_func.next();
+            println!("Prueba de profundidad: {:?}", renderer.depth_func);
+        }
+
+        // Alterna entre proyección en perspectiva y ortográfica con la tecla O
+        if rl.is_key_pressed(KeyboardKey::KEY_O) {
+            projection = projection.toggled(camera.distance, camera.fov_y);
+            println!("Proyección: {}", projection.name());
+        }
+
+        // Reduce o amplía el campo de visión con las teclas [ y ]
+        for (key, delta) in [(KeyboardKey::KEY_LEFT_BRACKET, -5.0_f32), (KeyboardKey::KEY_RIGHT_BRACKET, 5.0)] {
+            if rl.is_key_pressed(key) && projection.is_perspective() {
+                camera.adjust_fov(delta.to_radians());
+                println!("Campo de visión: {:.0}°", camera.fov_y.to_degrees());
+            }
+        }
+
+        // Activa o desactiva la rotación de la luz con la tecla L
+        if rl.is_key_pressed(KeyboardKey::KEY_L) {
+            rotate_light = !rotate_light;
+            if !rotate_light {
+                renderer.lights[0].dir = base_light_dir;
+            }
+        }
+
+        // Agrega o quita la luz de relleno con la tecla K
+        if rl.is_key_pressed(KeyboardKey::KEY_K) {
+            if renderer.lights.len() > 1 {
+                renderer.lights.truncate(1);
             } else {
-                paused_time = time;
-                paused = true;
+                renderer.lights.push(fill_light);
             }
         }
 
-        if !paused {
-            last_active_time = time;
+        // Muestra u oculta el fondo de estrellas con la tecla B
+        if rl.is_key_pressed(KeyboardKey::KEY_B) {
+            show_stars = !show_stars;
         }
 
-        // Actualizar órbitas de lunas
-        let orbit_radius = 2.5;
-        let orbit_speed = 0.5;
+        // Muestra u oculta las estelas de las órbitas con la tecla T
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            show_orbits = !show_orbits;
+        }
 
-        if current_scene == 3 || current_scene == 4 {
-            if let Some(moon) = scenes[current_scene].get_mut(1) {
-                moon.position = Vec3::new(
-                    (time * orbit_speed).cos() * orbit_radius,
-                    (time * orbit_speed * 0.7).sin() * 0.3,
-                    (time * orbit_speed).sin() * orbit_radius,
-                );
-            }
+        // Activa o desactiva el bloom con la tecla H
+        if rl.is_key_pressed(KeyboardKey::KEY_H) {
+            bloom_enabled = !bloom_enabled;
+            println!("Bloom: {}", if bloom_enabled { "activado" } else { "desactivado" });
+        }
+
+        // Activa o desactiva el mapa de sombras con la tecla J; sin él quedan las sombras analíticas.
+        if rl.is_key_pressed(KeyboardKey::KEY_J) && args.shadow_map_size > 0 {
+            renderer.shadow_map = match renderer.shadow_map {
+                Some(_) => None,
+                None => Some(ShadowMap::new(args.shadow_map_size)),
+            };
+            println!("Mapa de sombras: {}", if renderer.shadow_map.is_some() { "activado" } else { "desactivado" });
+        }
+
+        // Activa o desactiva el suavizado de bordes por cobertura con la tecla N
+        if rl.is_key_pressed(KeyboardKey::KEY_N) {
+            renderer.edge_antialiasing = !renderer.edge_antialiasing;
+            println!("Suavizado de bordes: {}", if renderer.edge_antialiasing { "activado" } else { "desactivado" });
+        }
+
+        // Activa o desactiva la corrección gamma (sRGB) con la tecla G
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            gamma_correct = !gamma_correct;
+            println!("Corrección gamma: {}", if gamma_correct { "activada" } else { "desactivada" });
+        }
+
+        // Arrastrar con el botón izquierdo del ratón gira la cámara alrededor de la escena (en modo libre, la mirada)
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            let delta = rl.get_mouse_delta();
+            camera.orbit(-delta.x * orbit_sensitivity, delta.y * orbit_sensitivity);
+        }
+
+        // La rueda del ratón acerca o aleja la cámara (en modo libre cambia la velocidad)
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            camera.zoom(wheel);
+        }
+
+        // Alterna entre la cámara orbital y la libre con la tecla F
+        if rl.is_key_pressed(KeyboardKey::KEY_F) {
+            camera.toggle_mode();
+            println!("Cámara: {}", camera.mode.name());
+        }
+
+        // En modo libre, WASD mueve la cámara hacia donde mira y Q/E la bajan o suben.
+        // Usa el tiempo real del cuadro para que la cámara se mueva aunque la simulación esté en pausa.
+        if camera.mode == CameraMode::Fly {
+            let axis = |positive: KeyboardKey, negative: KeyboardKey| {
+                rl.is_key_down(positive) as i32 as f32 - rl.is_key_down(negative) as i32 as f32
+            };
+            let direction = Vec3::new(
+                axis(KeyboardKey::KEY_D, KeyboardKey::KEY_A),
+                axis(KeyboardKey::KEY_E, KeyboardKey::KEY_Q),
+                axis(KeyboardKey::KEY_W, KeyboardKey::KEY_S),
+            );
+            camera.fly(direction, rl.get_frame_time());
+        }
+
+        // Pausa
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            clock.toggle_pause();
+        }
+
+        // Velocidad de la simulación: + la duplica y - la reduce a la mitad
+        if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) || rl.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+            clock.scale_speed(2.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_MINUS) || rl.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT) {
+            clock.scale_speed(0.5);
         }
+
+        // Actualizar órbitas de planetas y lunas
+        update_orbits(&mut scenes[current_scene], time);
         
-        let view_matrix = look_at(
-            &Vec3::new(0.0, 0.0, 3.5),
-            &Vec3::new(0.0, 0.0, 0.0),
-            &Vec3::new(0.0, 1.0, 0.0),
-        );
+        // La luz gira alrededor del eje Y para simular el movimiento del sol.
+        if rotate_light {
+            renderer.lights[0].dir = rotate_vec3(&base_light_dir, time * 0.5, &Vec3::new(0.0, 1.0, 0.0));
+        }
 
-        let projection_matrix = perspective(
-            WIDTH as f32 / HEIGHT as f32,
-            60.0_f32.to_radians(),
-            0.1,
-            100.0,
+        // Las estadísticas se reinician cada cuadro y suman las de todos los objetos.
+        renderer.background = background(current_scene);
+        let stats = render_scene(
+            &mut renderer,
+            &mut framebuffer,
+            &scenes[current_scene],
+            &camera,
+            &projection,
+            time,
+            cull_backfaces,
         );
 
-        framebuffer.clear(Color::BLACK);
+        // Reduce el búfer supermuestreado a la resolución de la ventana.
+        framebuffer.downsample_into(&mut display, renderer.supersample);
 
-        for obj in &scenes[current_scene] {
-            let model_matrix = obj.get_model_matrix(time);
+        // El bloom se aplica sobre la geometría ya reducida, antes de agregar las estrellas.
+        if bloom_enabled && let Some(bloom) = bloom_settings.get(current_scene) {
+            display.bloom(bloom.threshold, bloom.blur_radius, bloom.intensity);
+        }
 
-            renderer.render_mesh(
-                &mut framebuffer,
-                &obj.mesh,
-                obj.shader.as_ref(),
-                &model_matrix,
-                &view_matrix,
-                &projection_matrix,
-                time,
-            );
+        // Las estelas, igual que las estrellas, se dibujan a la resolución de la ventana para que el promedio
+        // no las atenúe.
+        if show_orbits {
+            let aspect = display.width as f32 / display.height.max(1) as f32;
+            let view_projection = projection.matrix(aspect, camera.fov_y) * camera.view_matrix();
+            let samples = args.orbit_samples as usize;
+            draw_orbit_trails(&mut display, &scenes[current_scene], &Mat4::identity(), &view_projection, samples);
+        }
+
+        // Las estrellas se dibujan a la resolución de la ventana para que el promedio no las atenúe.
+        if show_stars {
+            starfield.draw(&mut display, time);
+        }
+
+        // Los sombreadores calculan en espacio lineal; la corrección gamma codifica el resultado en sRGB.
+        if gamma_correct {
+            display.encode_srgb();
         }
 
-        if let Err(e) = texture.update_texture(framebuffer.as_bytes()) {
+        // Guarda una captura del framebuffer con la tecla P
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            let path = format!("screenshot_{}.png", millis);
+            match display.save_png(&path) {
+                Ok(()) => println!("✓ Captura guardada en {}", path),
+                Err(e) => eprintln!("⚠ No se pudo guardar la captura: {}", e),
+            }
+        }
+
+        if let Err(e) = texture.update_texture(display.as_bytes()) {
             eprintln!("Error actualizando textura: {:?}", e);
         }
 
@@ -305,9 +1280,14 @@ fn main() {
 
         d.draw_fps(10, 10);
 
-        let status = if paused { " [PAUSADO]" } else { "" };
+        let status = if clock.is_paused() { " [PAUSADO]" } else { "" };
         d.draw_text(
-            &format!("{}{}", shader_names[current_scene], status),
+            &format!(
+                "{} | Velocidad: x{}{}",
+                shader_names.get(current_scene).map_or("", |name| name.as_str()),
+                clock.time_scale(),
+                status
+            ),
             10,
             35,
             20,
@@ -321,24 +1301,75 @@ fn main() {
             "Modo: Procedural" 
         };
         d.draw_text(
-            mesh_type,
+            &format!(
+                "{} | Proyección: {}{} | Cámara: {} | Sombreado: {:?}{}",
+                mesh_type,
+                projection.name(),
+                if projection.is_perspective() { format!(" {:.0}°", camera.fov_y.to_degrees()) } else { String::new() },
+                camera.mode.name(),
+                renderer.shading_model,
+                if flat_shading { " facetado" } else { "" }
+            ),
             10,
             60,
             16,
             raylib::color::Color::YELLOW,
         );
 
-        // Controles actualizados
+        // Estadísticas del renderizador en este cuadro.
+        d.draw_text(
+            &format!(
+                "Triángulos: {} enviados, {} dibujados, {} descartados, {} recortados | Píxeles: {}",
+                stats.submitted, stats.drawn, stats.culled, stats.clipped, stats.pixels_written
+            ),
+            10,
+            80,
+            16,
+            raylib::color::Color::LIGHTGRAY,
+        );
+
+        // Controles actualizados; el rango de teclas numéricas depende de cuántas escenas hay.
+        let scene_range = match scenes.len().min(scene_keys.len()) {
+            10 => "1-9, 0".to_string(),
+            n => format!("1-{}", n),
+        };
         let controls = if obj_sphere.is_some() {
-            "Controles: 1-5 = Planetas, SPACE = Pausa, M = Cambiar Malla, ESC = Salir"
+            format!("Controles: {} = Planetas, SPACE = Pausa, +/- = Velocidad, M = Cambiar Malla, P = Captura, ESC = Salir", scene_range)
         } else {
-            "Controles: 1-5 = Planetas, SPACE = Pausa, ESC = Salir"
+            format!("Controles: {} = Planetas, SPACE = Pausa, +/- = Velocidad, P = Captura, ESC = Salir", scene_range)
         };
         
         d.draw_text(
-            controls,
+            &controls,
+            10,
+            display.height as i32 - 85,
+            16,
+            raylib::color::Color::LIGHTGRAY,
+        );
+
+        // Controles de la cámara
+        d.draw_text(
+            "Cámara: Arrastrar = Orbitar, Rueda = Zoom, O = Proyección",
+            10,
+            display.height as i32 - 65,
+            16,
+            raylib::color::Color::LIGHTGRAY,
+        );
+
+        // Controles del renderizador
+        d.draw_text(
+            "Render: C = Culling, R = Modo, U = Facetas, Z = Profundidad, L = Girar Luz, K = Luz de Relleno, J = Sombras",
+            10,
+            display.height as i32 - 45,
+            16,
+            raylib::color::Color::LIGHTGRAY,
+        );
+
+        // Controles de la imagen final
+        d.draw_text(
+            "Imagen: B = Estrellas, T = Órbitas, G = Gamma, N = Suavizado de Bordes, H = Bloom",
             10,
-            HEIGHT as i32 - 25,
+            display.height as i32 - 25,
             16,
             raylib::color::Color::LIGHTGRAY,
         );
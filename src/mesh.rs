@@ -2,20 +2,51 @@
 use nalgebra_glm::{Vec2, Vec3};
 // Importa la constante PI para cálculos matemáticos.
 use std::f32::consts::PI;
+// Importa HashMap para compartir vértices entre triángulos al subdividir.
+use std::collections::HashMap;
+// Para escribir mallas de vuelta a archivos .obj.
+use std::io::{BufWriter, Write};
 
-// Define la estructura de un vértice, que contiene su posición, normal y coordenadas de textura (UV).
+// Define la estructura de un vértice, que contiene su posición, normal, coordenadas de textura (UV) y tangente.
 #[derive(Debug, Clone)]
 pub struct Vertex {
     pub position: Vec3, // Posición del vértice en el espacio 3D.
     pub normal: Vec3,   // Vector normal del vértice, usado para la iluminación.
-    pub uv: Vec2,       // Coordenadas de textura (UV) para mapear texturas sobre el objeto.
+    pub uv: Vec2,       // Coordenadas de textura (UV); V = 0 es el borde superior de la imagen.
+    pub tangent: Vec3,  // Dirección en la que crece U sobre la superficie; cero si se desconoce.
 }
 
 // Define una malla de objeto, que consiste en una lista de vértices y una lista de índices que forman las caras.
+// Los triángulos siguen la convención estándar de los archivos .obj: orden antihorario visto desde afuera.
 #[derive(Clone)]
 pub struct ObjMesh {
-    pub vertices: Vec<Vertex>, // Lista de todos los vértices en la malla.
-    pub indices: Vec<u32>,     // Lista de índices que definen los triángulos de la malla.
+    pub vertices: Vec<Vertex>,      // Lista de todos los vértices en la malla.
+    pub indices: Vec<u32>,          // Lista de índices que definen los triángulos de la malla.
+    pub material: Option<Material>, // Material del archivo .mtl asociado, si lo hay.
+}
+
+// Define el material de una malla tal como lo describe un archivo .mtl.
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: String,   // Nombre del material en el archivo .mtl.
+    pub diffuse: Vec3,  // Color difuso (Kd).
+    pub specular: Vec3, // Color especular (Ks).
+    pub shininess: f32, // Exponente especular (Ns).
+}
+
+impl Material {
+    // Convierte un material leído por `tobj`, usando valores neutros para los campos que falten.
+    fn from_tobj(material: &tobj::Material) -> Self {
+        let [dr, dg, db] = material.diffuse.unwrap_or([0.8, 0.8, 0.8]);
+        let [sr, sg, sb] = material.specular.unwrap_or([0.0, 0.0, 0.0]);
+
+        Material {
+            name: material.name.clone(),
+            diffuse: Vec3::new(dr, dg, db),
+            specular: Vec3::new(sr, sg, sb),
+            shininess: material.shininess.unwrap_or(0.0),
+        }
+    }
 }
 
 impl ObjMesh {
@@ -29,6 +60,7 @@ impl ObjMesh {
             position: Vec3::new(0.0, radius, 0.0),
             normal: Vec3::new(0.0, 1.0, 0.0),
             uv: Vec2::new(0.5, 0.0),
+            tangent: Vec3::new(1.0, 0.0, 0.0),
         });
 
         // Genera los vértices intermedios de la esfera, excluyendo los polos.
@@ -44,8 +76,10 @@ impl ObjMesh {
                 let position = Vec3::new(x * radius, y * radius, z * radius);
                 let normal = Vec3::new(x, y, z);
                 let uv = Vec2::new(s as f32 / sectors as f32, r as f32 / rings as f32);
+                // La tangente sigue la dirección de la longitud, que es hacia donde crece U.
+                let tangent = Vec3::new(-phi.sin(), 0.0, phi.cos());
 
-                vertices.push(Vertex { position, normal, uv });
+                vertices.push(Vertex { position, normal, uv, tangent });
             }
         }
 
@@ -54,13 +88,14 @@ impl ObjMesh {
             position: Vec3::new(0.0, -radius, 0.0),
             normal: Vec3::new(0.0, -1.0, 0.0),
             uv: Vec2::new(0.5, 1.0),
+            tangent: Vec3::new(1.0, 0.0, 0.0),
         });
 
         // Genera los índices para los triángulos que conectan con el polo norte.
         for s in 0..sectors {
             indices.push(0); // Polo norte.
-            indices.push(1 + s);
             indices.push(1 + s + 1);
+            indices.push(1 + s);
         }
 
         // Genera los índices para las bandas de quads (dos triángulos) intermedias.
@@ -70,12 +105,12 @@ impl ObjMesh {
                 let next = current + sectors + 1;
 
                 indices.push(current);
-                indices.push(next);
                 indices.push(current + 1);
+                indices.push(next);
 
                 indices.push(current + 1);
-                indices.push(next);
                 indices.push(next + 1);
+                indices.push(next);
             }
         }
 
@@ -85,23 +120,370 @@ impl ObjMesh {
 
         for s in 0..sectors {
             indices.push(last_ring_start + s);
-            indices.push(south_pole_index);
             indices.push(last_ring_start + s + 1);
+            indices.push(south_pole_index);
+        }
+
+        ObjMesh { vertices, indices, material: None }
+    }
+
+    // Genera un cubo centrado en el origen con arista `size`.
+    // Cada cara tiene sus propios 4 vértices para que su normal apunte hacia afuera y sus UV cubran [0, 1].
+    pub fn create_cube(size: f32) -> Self {
+        let half = size * 0.5;
+        let mut vertices = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+
+        // Cada cara se describe con su normal y dos ejes tangentes (u, v) tales que u × v = normal.
+        let faces = [
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+            (Vec3::new(0.0, -1.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            (Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(0.0, 0.0, -1.0), Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+        ];
+
+        for (normal, u, v) in faces {
+            let base = vertices.len() as u32;
+            let center = normal * half;
+
+            // Esquinas de la cara en orden antihorario visto desde afuera.
+            let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+            for (su, sv) in corners {
+                vertices.push(Vertex {
+                    position: center + u * (su * half) + v * (sv * half),
+                    normal,
+                    uv: Vec2::new((su + 1.0) * 0.5, (1.0 - sv) * 0.5),
+                    tangent: u,
+                });
+            }
+
+            // Dos triángulos por cara, en el mismo orden antihorario.
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        ObjMesh { vertices, indices, material: None }
+    }
+
+    // Genera un toro en el plano XZ, centrado en el origen.
+    // `major_radius` es la distancia del centro al eje del tubo y `minor_radius` el radio del tubo.
+    // Las costuras duplican vértices (con UV 0 y 1) para que la textura no se estire, igual que en la esfera.
+    pub fn create_torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> Self {
+        let mut vertices = Vec::with_capacity(((major_segments + 1) * (minor_segments + 1)) as usize);
+        let mut indices = Vec::with_capacity((major_segments * minor_segments * 6) as usize);
+
+        // Genera los vértices recorriendo el anillo mayor y, en cada paso, la sección circular del tubo.
+        for i in 0..=major_segments {
+            let theta = 2.0 * PI * i as f32 / major_segments as f32;
+            let tube_center = Vec3::new(theta.cos() * major_radius, 0.0, theta.sin() * major_radius);
+
+            for j in 0..=minor_segments {
+                let phi = 2.0 * PI * j as f32 / minor_segments as f32;
+
+                // La normal apunta desde el centro del tubo hacia el vértice.
+                let normal = Vec3::new(phi.cos() * theta.cos(), phi.sin(), phi.cos() * theta.sin());
+
+                vertices.push(Vertex {
+                    position: tube_center + normal * minor_radius,
+                    normal,
+                    uv: Vec2::new(i as f32 / major_segments as f32, j as f32 / minor_segments as f32),
+                    tangent: Vec3::new(-theta.sin(), 0.0, theta.cos()),
+                });
+            }
+        }
+
+        // Genera dos triángulos por cada quad de la superficie, en orden antihorario visto desde afuera.
+        for i in 0..major_segments {
+            for j in 0..minor_segments {
+                let current = i * (minor_segments + 1) + j;
+                let next = current + minor_segments + 1;
+
+                indices.push(current);
+                indices.push(current + 1);
+                indices.push(next);
+
+                indices.push(current + 1);
+                indices.push(next + 1);
+                indices.push(next);
+            }
+        }
+
+        ObjMesh { vertices, indices, material: None }
+    }
+
+    // Genera un cilindro vertical centrado en el origen, con tapas opcionales.
+    // Se usan al menos 3 segmentos para que la malla nunca sea degenerada.
+    pub fn create_cylinder(radius: f32, height: f32, segments: u32, capped: bool) -> Self {
+        let segments = segments.max(3);
+        let half_height = height * 0.5;
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        // Genera la pared lateral: un círculo superior y uno inferior con normales radiales.
+        // La coordenada U recorre la longitud igual que en la esfera.
+        for ring in 0..=1 {
+            let y = if ring == 0 { half_height } else { -half_height };
+
+            for s in 0..=segments {
+                let angle = 2.0 * PI * s as f32 / segments as f32;
+                let normal = Vec3::new(angle.cos(), 0.0, angle.sin());
+
+                vertices.push(Vertex {
+                    position: Vec3::new(normal.x * radius, y, normal.z * radius),
+                    normal,
+                    uv: Vec2::new(s as f32 / segments as f32, ring as f32),
+                    tangent: Vec3::new(-angle.sin(), 0.0, angle.cos()),
+                });
+            }
+        }
+
+        for s in 0..segments {
+            let current = s;
+            let next = current + segments + 1;
+
+            indices.push(current);
+            indices.push(current + 1);
+            indices.push(next);
+
+            indices.push(current + 1);
+            indices.push(next + 1);
+            indices.push(next);
         }
 
-        ObjMesh { vertices, indices }
+        // Genera las tapas como abanicos de triángulos con normales a lo largo de ±Y.
+        if capped {
+            for (y, normal_y) in [(half_height, 1.0), (-half_height, -1.0)] {
+                let center = vertices.len() as u32;
+                let normal = Vec3::new(0.0, normal_y, 0.0);
+
+                vertices.push(Vertex {
+                    position: Vec3::new(0.0, y, 0.0),
+                    normal,
+                    uv: Vec2::new(0.5, 0.5),
+                    tangent: Vec3::new(1.0, 0.0, 0.0),
+                });
+
+                for s in 0..=segments {
+                    let angle = 2.0 * PI * s as f32 / segments as f32;
+                    vertices.push(Vertex {
+                        position: Vec3::new(angle.cos() * radius, y, angle.sin() * radius),
+                        normal,
+                        uv: Vec2::new(0.5 + angle.cos() * 0.5, 0.5 + angle.sin() * 0.5),
+                        tangent: Vec3::new(1.0, 0.0, 0.0),
+                    });
+                }
+
+                // La tapa inferior invierte el orden para seguir siendo antihoraria vista desde afuera.
+                for s in 0..segments {
+                    let rim = center + 1 + s;
+                    if normal_y > 0.0 {
+                        indices.extend_from_slice(&[center, rim + 1, rim]);
+                    } else {
+                        indices.extend_from_slice(&[center, rim, rim + 1]);
+                    }
+                }
+            }
+        }
+
+        ObjMesh { vertices, indices, material: None }
     }
 
-    // Carga una malla desde un archivo en formato .obj.
+    // Genera un plano horizontal en XZ centrado en el origen, de `width` en X por `depth` en Z, con las normales
+    // hacia +Y. Se divide en una rejilla de `subdivisions_x` por `subdivisions_z` quads (al menos uno por eje),
+    // para que la iluminación por vértice o un desplazamiento tengan vértices suficientes. Las UV cubren [0, 1].
+    pub fn create_plane(width: f32, depth: f32, subdivisions_x: u32, subdivisions_z: u32) -> Self {
+        let (sx, sz) = (subdivisions_x.max(1), subdivisions_z.max(1));
+        let mut vertices = Vec::with_capacity(((sx + 1) * (sz + 1)) as usize);
+        let mut indices = Vec::with_capacity((sx * sz * 6) as usize);
+
+        for j in 0..=sz {
+            for i in 0..=sx {
+                let (u, v) = (i as f32 / sx as f32, j as f32 / sz as f32);
+                vertices.push(Vertex {
+                    position: Vec3::new((u - 0.5) * width, 0.0, (v - 0.5) * depth),
+                    normal: Vec3::new(0.0, 1.0, 0.0),
+                    uv: Vec2::new(u, v),
+                    tangent: Vec3::new(1.0, 0.0, 0.0),
+                });
+            }
+        }
+
+        // Dos triángulos por quad, en orden antihorario visto desde arriba.
+        for j in 0..sz {
+            for i in 0..sx {
+                let current = j * (sx + 1) + i;
+                let next = current + sx + 1;
+
+                indices.push(current);
+                indices.push(next);
+                indices.push(current + 1);
+
+                indices.push(current + 1);
+                indices.push(next);
+                indices.push(next + 1);
+            }
+        }
+
+        ObjMesh { vertices, indices, material: None }
+    }
+
+    // Genera un terreno a partir de una imagen en escala de grises: cada píxel es un vértice de la rejilla y su
+    // luminancia (0 = negro, 1 = blanco) es la altura. `scale.x` y `scale.z` son el ancho y la profundidad del
+    // terreno completo y `scale.y` la altura máxima. La rejilla sigue la forma de la imagen, así que las imágenes
+    // no cuadradas conservan su proporción si la escala también lo hace.
+    pub fn from_heightmap(path: &str, scale: Vec3) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("Error loading heightmap '{}': {}", path, e))?
+            .to_luma16();
+
+        let (width, height) = (image.width(), image.height());
+        if width < 2 || height < 2 {
+            return Err(format!(
+                "Error loading heightmap '{}': image must be at least 2x2 pixels, got {}x{}",
+                path, width, height
+            ));
+        }
+
+        // La rejilla del plano tiene un vértice por píxel, en el mismo orden de filas que la imagen.
+        let mut mesh = Self::create_plane(scale.x, scale.z, width - 1, height - 1);
+        let heights: Vec<f32> = image.pixels().map(|p| p[0] as f32 / u16::MAX as f32 * scale.y).collect();
+        for (vertex, &h) in mesh.vertices.iter_mut().zip(&heights) {
+            vertex.position.y = h;
+        }
+
+        // Normales suaves por diferencias centrales entre los vecinos; en los bordes se usa el propio píxel.
+        let (w, h) = (width as usize, height as usize);
+        let step_x = scale.x / (w - 1) as f32;
+        let step_z = scale.z / (h - 1) as f32;
+        let height_at = |i: usize, j: usize| heights[j * w + i];
+        for j in 0..h {
+            for i in 0..w {
+                let (left, right) = (i.saturating_sub(1), (i + 1).min(w - 1));
+                let (back, front) = (j.saturating_sub(1), (j + 1).min(h - 1));
+                let dx = (height_at(right, j) - height_at(left, j)) / ((right - left) as f32 * step_x);
+                let dz = (height_at(i, front) - height_at(i, back)) / ((front - back) as f32 * step_z);
+
+                let vertex = &mut mesh.vertices[j * w + i];
+                vertex.normal = Vec3::new(-dx, 1.0, -dz).normalize();
+                vertex.tangent = orthonormalize(Vec3::new(1.0, 0.0, 0.0), vertex.normal);
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    // Genera una icoesfera: un icosaedro cuyos triángulos se subdividen `subdivisions` veces
+    // y cuyos vértices se proyectan sobre la esfera. Reparte los triángulos de forma uniforme, sin acumularlos en los polos.
+    pub fn create_icosphere(radius: f32, subdivisions: u32) -> Self {
+        let t = (1.0 + 5.0_f32.sqrt()) * 0.5;
+
+        // Los 12 vértices del icosaedro, proyectados sobre la esfera unitaria.
+        let mut positions: Vec<Vec3> = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+        .collect();
+
+        // Las 20 caras, en orden antihorario visto desde afuera.
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        // Divide cada triángulo en cuatro. Los puntos medios se guardan por arista para que
+        // los triángulos vecinos los compartan y la malla siga siendo cerrada.
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut next = Vec::with_capacity(triangles.len() * 4);
+
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| -> u32 {
+                let key = (a.min(b), a.max(b));
+                *midpoints.entry(key).or_insert_with(|| {
+                    positions.push(((positions[a as usize] + positions[b as usize]) * 0.5).normalize());
+                    positions.len() as u32 - 1
+                })
+            };
+
+            for [a, b, c] in triangles {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+
+                next.push([a, ab, ca]);
+                next.push([b, bc, ab]);
+                next.push([c, ca, bc]);
+                next.push([ab, bc, ca]);
+            }
+
+            triangles = next;
+        }
+
+        // En una esfera la normal coincide con la posición normalizada; las UV usan longitud y latitud.
+        // La tangente sigue la longitud; en los polos, donde no está definida, se usa +X como en la esfera UV.
+        let vertices = positions
+            .into_iter()
+            .map(|normal| Vertex {
+                position: normal * radius,
+                normal,
+                uv: Vec2::new(
+                    normal.z.atan2(normal.x) / (2.0 * PI) + 0.5,
+                    normal.y.clamp(-1.0, 1.0).acos() / PI,
+                ),
+                tangent: Vec3::new(-normal.z, 0.0, normal.x)
+                    .try_normalize(1e-6)
+                    .unwrap_or_else(|| Vec3::new(1.0, 0.0, 0.0)),
+            })
+            .collect();
+
+        ObjMesh {
+            vertices,
+            indices: triangles.into_iter().flatten().collect(),
+            material: None,
+        }
+    }
+
+    // Carga una malla desde un archivo en formato .obj. Solo usa el primer modelo del archivo.
     pub fn load_from_obj(path: &str) -> Result<Self, String> {
-        let (models, _) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
-            .map_err(|e| format!("Error loading OBJ: {}", e))?;
+        let (models, materials) = load_obj_models(path)?;
+        Ok(Self::from_tobj(&models[0].mesh, &materials))
+    }
+
+    // Carga todos los modelos de un archivo .obj, una malla por modelo.
+    pub fn load_all_from_obj(path: &str) -> Result<Vec<Self>, String> {
+        let (models, materials) = load_obj_models(path)?;
+        Ok(models.iter().map(|model| Self::from_tobj(&model.mesh, &materials)).collect())
+    }
+
+    // Carga todos los modelos de un archivo .obj y los une en una sola malla.
+    pub fn load_merged_from_obj(path: &str) -> Result<Self, String> {
+        let meshes = Self::load_all_from_obj(path)?;
+        Ok(Self::merge(&meshes))
+    }
+
+    // Une varias mallas en una sola, desplazando los índices de cada una según los vértices que la preceden.
+    // El resultado conserva el primer material encontrado, ya que una malla solo tiene uno.
+    pub fn merge(meshes: &[ObjMesh]) -> Self {
+        let mut vertices = Vec::with_capacity(meshes.iter().map(|mesh| mesh.vertices.len()).sum());
+        let mut indices = Vec::with_capacity(meshes.iter().map(|mesh| mesh.indices.len()).sum());
 
-        if models.is_empty() {
-            return Err("No models found in OBJ file".to_string());
+        for mesh in meshes {
+            let offset = vertices.len() as u32;
+            vertices.extend(mesh.vertices.iter().cloned());
+            indices.extend(mesh.indices.iter().map(|index| index + offset));
         }
 
-        let mesh = &models[0].mesh;
+        let material = meshes.iter().find_map(|mesh| mesh.material.clone());
+        ObjMesh { vertices, indices, material }
+    }
+
+    // Convierte una malla leída por `tobj` en un `ObjMesh`, completando normales y tangentes si faltan.
+    fn from_tobj(mesh: &tobj::Mesh, materials: &[tobj::Material]) -> Self {
         let mut vertices = Vec::new();
 
         for i in 0..mesh.positions.len() / 3 {
@@ -111,6 +493,7 @@ impl ObjMesh {
                 mesh.positions[i * 3 + 2],
             );
 
+            // Si el archivo no trae normales se calculan después a partir de las caras.
             let normal = if !mesh.normals.is_empty() {
                 Vec3::new(
                     mesh.normals[i * 3],
@@ -119,24 +502,346 @@ impl ObjMesh {
                 )
                 .normalize()
             } else {
-                position.normalize()
+                Vec3::zeros()
             };
 
+            // En los .obj V crece hacia arriba; se invierte para usar la misma convención que las mallas
+            // procedurales y las texturas, donde V = 0 es la fila superior.
             let uv = if !mesh.texcoords.is_empty() {
-                Vec2::new(mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1])
+                Vec2::new(mesh.texcoords[i * 2], 1.0 - mesh.texcoords[i * 2 + 1])
             } else {
                 Vec2::new(0.0, 0.0)
             };
 
-            vertices.push(Vertex { position, normal, uv });
+            vertices.push(Vertex { position, normal, uv, tangent: Vec3::zeros() });
         }
 
-        Ok(ObjMesh {
+        let mut result = ObjMesh {
             vertices,
             indices: mesh.indices.clone(),
+            material: mesh.material_id.and_then(|id| materials.get(id)).map(Material::from_tobj),
+        };
+
+        if mesh.normals.is_empty() {
+            result.recompute_normals();
+        }
+
+        // Sin UV no hay una dirección tangente significativa, así que se deja en cero.
+        if !mesh.texcoords.is_empty() {
+            result.compute_tangents();
+        }
+
+        result
+    }
+
+    // Guarda la malla en un archivo .obj. Cada vértice tiene su propia posición, UV y normal,
+    // así que las caras usan el mismo índice (base 1) para las tres: `f i/i/i`.
+    // Es una ayuda para depurar los generadores procedurales; la usa `--dump-obj`.
+    pub fn save_to_obj(&self, path: &str) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|e| format!("Error creating {}: {}", path, e))?;
+        self.write_obj(&mut BufWriter::new(file))
+            .map_err(|e| format!("Error writing {}: {}", path, e))
+    }
+
+    fn write_obj(&self, out: &mut impl Write) -> std::io::Result<()> {
+        for vertex in &self.vertices {
+            writeln!(out, "v {} {} {}", vertex.position.x, vertex.position.y, vertex.position.z)?;
+        }
+        for vertex in &self.vertices {
+            writeln!(out, "vt {} {}", vertex.uv.x, 1.0 - vertex.uv.y)?;
+        }
+        for vertex in &self.vertices {
+            writeln!(out, "vn {} {} {}", vertex.normal.x, vertex.normal.y, vertex.normal.z)?;
+        }
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (triangle[0] + 1, triangle[1] + 1, triangle[2] + 1);
+            writeln!(out, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+        }
+        out.flush()
+    }
+
+    // Recalcula normales suavizadas: cada vértice recibe la suma de las normales de sus caras,
+    // ponderadas por área (el producto cruz sin normalizar ya es proporcional al área), y luego se normaliza.
+    pub fn recompute_normals(&mut self) {
+        for vertex in &mut self.vertices {
+            vertex.normal = Vec3::zeros();
+        }
+
+        for triangle in self.indices.chunks_exact(3) {
+            let (i0, i1, i2) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+            if i0 >= self.vertices.len() || i1 >= self.vertices.len() || i2 >= self.vertices.len() {
+                continue;
+            }
+
+            let p0 = self.vertices[i0].position;
+            let face_normal = (self.vertices[i1].position - p0).cross(&(self.vertices[i2].position - p0));
+
+            self.vertices[i0].normal += face_normal;
+            self.vertices[i1].normal += face_normal;
+            self.vertices[i2].normal += face_normal;
+        }
+
+        for vertex in &mut self.vertices {
+            // Los vértices sin caras (o con caras degeneradas) conservan una normal nula en lugar de NaN.
+            if vertex.normal.magnitude_squared() > 0.0 {
+                vertex.normal = vertex.normal.normalize();
+            }
+        }
+    }
+
+    // Devuelve las esquinas mínima y máxima de la caja alineada a los ejes que contiene todos los vértices.
+    // Una malla vacía devuelve ambas esquinas en el origen. La cámara la usa para alejarse hasta que la escena
+    // quepa en la vista.
+    pub fn bounding_box(&self) -> (Vec3, Vec3) {
+        let Some(first) = self.vertices.first() else {
+            return (Vec3::zeros(), Vec3::zeros());
+        };
+
+        self.vertices.iter().fold((first.position, first.position), |(min, max), vertex| {
+            (min.inf(&vertex.position), max.sup(&vertex.position))
         })
     }
 
+    // Devuelve el promedio de las posiciones de los vértices (el origen si la malla está vacía).
+    pub fn centroid(&self) -> Vec3 {
+        if self.vertices.is_empty() {
+            return Vec3::zeros();
+        }
+
+        let sum = self.vertices.iter().fold(Vec3::zeros(), |acc, vertex| acc + vertex.position);
+        sum / self.vertices.len() as f32
+    }
+
+    // Devuelve el centro y el radio de una esfera que contiene todos los vértices: el centroide y la distancia
+    // al vértice más lejano. No es la esfera mínima, pero basta para encuadrar la malla o descartarla si queda
+    // fuera de la vista. Una malla vacía devuelve radio 0 en el origen.
+    pub fn bounding_sphere(&self) -> (Vec3, f32) {
+        let center = self.centroid();
+        let radius = self
+            .vertices
+            .iter()
+            .map(|vertex| (vertex.position - center).magnitude())
+            .fold(0.0, f32::max);
+        (center, radius)
+    }
+
+    // Traslada la malla para que su centroide quede en el origen, de modo que rote sobre sí misma.
+    pub fn center(&mut self) {
+        let centroid = self.centroid();
+        for vertex in &mut self.vertices {
+            vertex.position -= centroid;
+        }
+    }
+
+    // Escala la malla alrededor de su centroide para que el vértice más lejano quede a `target_radius`.
+    // Las normales no cambian porque la escala es uniforme. Una malla sin extensión se deja igual.
+    pub fn normalize_scale(&mut self, target_radius: f32) {
+        let (centroid, max_radius) = self.bounding_sphere();
+
+        if max_radius <= f32::EPSILON {
+            return;
+        }
+
+        let factor = target_radius / max_radius;
+        for vertex in &mut self.vertices {
+            vertex.position = centroid + (vertex.position - centroid) * factor;
+        }
+    }
+
+    // Divide cada triángulo en cuatro insertando un vértice en el punto medio de cada arista.
+    // Los puntos medios se guardan por arista, igual que en la icoesfera, para que los triángulos vecinos los compartan.
+    // Los triángulos con índices fuera de rango se descartan, ya que no tienen aristas de las que tomar el punto medio.
+    pub fn subdivide(&mut self) {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut indices = Vec::with_capacity(self.indices.len() * 4);
+        let count = self.vertices.len() as u32;
+        let vertices = &mut self.vertices;
+
+        let mut midpoint = |a: u32, b: u32| -> u32 {
+            let key = (a.min(b), a.max(b));
+            *midpoints.entry(key).or_insert_with(|| {
+                let (va, vb) = (&vertices[a as usize], &vertices[b as usize]);
+                let normal = (va.normal + vb.normal).try_normalize(1e-6).unwrap_or(va.normal);
+                let tangent = va.tangent + vb.tangent;
+                let vertex = Vertex {
+                    position: (va.position + vb.position) * 0.5,
+                    normal,
+                    uv: (va.uv + vb.uv) * 0.5,
+                    // Una tangente desconocida (cero) sigue siéndolo en el punto medio.
+                    tangent: if tangent == Vec3::zeros() { tangent } else { orthonormalize(tangent, normal) },
+                };
+                vertices.push(vertex);
+                vertices.len() as u32 - 1
+            })
+        };
+
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+            if a >= count || b >= count || c >= count {
+                continue;
+            }
+
+            let ab = midpoint(a, b);
+            let bc = midpoint(b, c);
+            let ca = midpoint(c, a);
+
+            // Los cuatro triángulos conservan el orden antihorario del original.
+            indices.extend_from_slice(&[a, ab, ca, b, bc, ab, c, ca, bc, ab, bc, ca]);
+        }
+
+        self.indices = indices;
+    }
+
+    // Une los vértices cuya posición, normal y UV coinciden dentro de `epsilon` y reconstruye los índices.
+    // Los vértices se agrupan en una rejilla de celdas de tamaño `epsilon`, así que solo se comparan
+    // con los de su celda y las vecinas. Los triángulos que quedan degenerados tras la unión se descartan.
+    pub fn weld_vertices(&mut self, epsilon: f32) {
+        let epsilon = epsilon.max(f32::EPSILON);
+        let cell_of = |p: &Vec3| {
+            (
+                (p.x / epsilon).floor() as i64,
+                (p.y / epsilon).floor() as i64,
+                (p.z / epsilon).floor() as i64,
+            )
+        };
+        let matches = |a: &Vertex, b: &Vertex| {
+            (a.position - b.position).amax() <= epsilon
+                && (a.normal - b.normal).amax() <= epsilon
+                && (a.uv - b.uv).amax() <= epsilon
+        };
+
+        let mut grid: HashMap<(i64, i64, i64), Vec<u32>> = HashMap::new();
+        let mut welded: Vec<Vertex> = Vec::with_capacity(self.vertices.len());
+        let mut remap = Vec::with_capacity(self.vertices.len());
+
+        for vertex in &self.vertices {
+            let (cx, cy, cz) = cell_of(&vertex.position);
+
+            let mut existing = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(candidates) = grid.get(&(cx + dx, cy + dy, cz + dz)) else {
+                            continue;
+                        };
+                        if let Some(&index) = candidates.iter().find(|&&i| matches(&welded[i as usize], vertex)) {
+                            existing = Some(index);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+
+            let index = existing.unwrap_or_else(|| {
+                welded.push(vertex.clone());
+                let index = welded.len() as u32 - 1;
+                grid.entry((cx, cy, cz)).or_default().push(index);
+                index
+            });
+            remap.push(index);
+        }
+
+        let mut indices = Vec::with_capacity(self.indices.len());
+        for triangle in self.indices.chunks_exact(3) {
+            // Se descartan los triángulos con índices fuera de rango, que no se podrían reasignar.
+            let (Some(&a), Some(&b), Some(&c)) = (
+                remap.get(triangle[0] as usize),
+                remap.get(triangle[1] as usize),
+                remap.get(triangle[2] as usize),
+            ) else {
+                continue;
+            };
+
+            if a != b && b != c && c != a {
+                indices.extend_from_slice(&[a, b, c]);
+            }
+        }
+
+        self.vertices = welded;
+        self.indices = indices;
+    }
+
+    // Devuelve una copia facetada de la malla: cada triángulo recibe sus propios tres vértices
+    // con la normal de la cara, de modo que la interpolación produce bordes duros.
+    // Los triángulos con índices fuera de rango se omiten.
+    pub fn make_flat(&self) -> Self {
+        let mut vertices = Vec::with_capacity(self.indices.len());
+        let mut indices = Vec::with_capacity(self.indices.len());
+
+        for triangle in self.indices.chunks_exact(3) {
+            let (Some(a), Some(b), Some(c)) = (
+                self.vertices.get(triangle[0] as usize),
+                self.vertices.get(triangle[1] as usize),
+                self.vertices.get(triangle[2] as usize),
+            ) else {
+                continue;
+            };
+            let corners = [a, b, c];
+
+            let face_normal = (corners[1].position - corners[0].position)
+                .cross(&(corners[2].position - corners[0].position));
+            // Un triángulo degenerado no tiene normal propia; se conserva la del primer vértice.
+            let normal = if face_normal.magnitude_squared() > 0.0 {
+                face_normal.normalize()
+            } else {
+                corners[0].normal
+            };
+
+            for corner in corners {
+                indices.push(vertices.len() as u32);
+                vertices.push(Vertex {
+                    position: corner.position,
+                    normal,
+                    uv: corner.uv,
+                    tangent: if corner.tangent == Vec3::zeros() {
+                        corner.tangent
+                    } else {
+                        orthonormalize(corner.tangent, normal)
+                    },
+                });
+            }
+        }
+
+        ObjMesh { vertices, indices, material: self.material.clone() }
+    }
+
+    // Calcula tangentes por vértice a partir de los gradientes de UV de cada triángulo.
+    // Las tangentes de las caras se acumulan por vértice y luego se ortonormalizan contra la normal.
+    pub fn compute_tangents(&mut self) {
+        for vertex in &mut self.vertices {
+            vertex.tangent = Vec3::zeros();
+        }
+
+        for triangle in self.indices.chunks_exact(3) {
+            let (i0, i1, i2) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+            if i0 >= self.vertices.len() || i1 >= self.vertices.len() || i2 >= self.vertices.len() {
+                continue;
+            }
+
+            let (v0, v1, v2) = (&self.vertices[i0], &self.vertices[i1], &self.vertices[i2]);
+            let edge1 = v1.position - v0.position;
+            let edge2 = v2.position - v0.position;
+            let duv1 = v1.uv - v0.uv;
+            let duv2 = v2.uv - v0.uv;
+
+            // Si las UV del triángulo son colineales no aportan información de dirección.
+            let det = duv1.x * duv2.y - duv2.x * duv1.y;
+            if det.abs() < 1e-8 {
+                continue;
+            }
+
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+
+            self.vertices[i0].tangent += tangent;
+            self.vertices[i1].tangent += tangent;
+            self.vertices[i2].tangent += tangent;
+        }
+
+        for vertex in &mut self.vertices {
+            vertex.tangent = orthonormalize(vertex.tangent, vertex.normal);
+        }
+    }
+
     // Genera un anillo plano con un número específico de segmentos.
     pub fn create_ring(inner_radius: f32, outer_radius: f32, segments: u32) -> Self {
         let mut vertices = Vec::new();
@@ -155,6 +860,7 @@ impl ObjMesh {
                     position: Vec3::new(x, 0.0, z),
                     normal: Vec3::new(0.0, 1.0, 0.0), // La normal apunta hacia arriba.
                     uv: Vec2::new(s as f32 / segments as f32, ring as f32),
+                    tangent: Vec3::new(-angle.sin(), 0.0, angle.cos()),
                 });
             }
         }
@@ -168,15 +874,331 @@ impl ObjMesh {
 
             // Primer triángulo del quad.
             indices.push(i0);
-            indices.push(i2);
             indices.push(i1);
+            indices.push(i2);
 
             // Segundo triángulo del quad.
             indices.push(i1);
-            indices.push(i2);
             indices.push(i3);
+            indices.push(i2);
+        }
+
+        ObjMesh { vertices, indices, material: None }
+    }
+}
+
+// Lee los modelos y materiales de un archivo .obj y falla si no contiene ningún modelo.
+// Un .mtl ausente o inválido no impide cargar la geometría; simplemente no habrá materiales.
+fn load_obj_models(path: &str) -> Result<(Vec<tobj::Model>, Vec<tobj::Material>), String> {
+    let (models, materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
+        .map_err(|e| format!("Error loading OBJ: {}", e))?;
+
+    if models.is_empty() {
+        return Err("No models found in OBJ file".to_string());
+    }
+
+    Ok((models, materials.unwrap_or_default()))
+}
+
+// Elimina de `tangent` su componente a lo largo de `normal` (Gram-Schmidt) y la normaliza.
+// Si no queda dirección útil se elige cualquier vector perpendicular a la normal.
+fn orthonormalize(tangent: Vec3, normal: Vec3) -> Vec3 {
+    let projected = tangent - normal * normal.dot(&tangent);
+    if let Some(t) = projected.try_normalize(1e-6) {
+        return t;
+    }
+
+    let axis = if normal.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    axis.cross(&normal).try_normalize(1e-6).unwrap_or(axis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    // Malla con las posiciones e índices dados; las normales quedan en +Z y las UV en cero.
+    fn mesh(positions: &[Vec3], indices: &[u32]) -> ObjMesh {
+        let vertices = positions
+            .iter()
+            .map(|&position| Vertex { position, normal: Vec3::z(), uv: Vec2::zeros(), tangent: Vec3::zeros() })
+            .collect();
+        ObjMesh { vertices, indices: indices.to_vec(), material: None }
+    }
+
+    // Una arista de cubo: un quad como piso (normal +Y) y otro como pared (normal +Z) que comparten los
+    // vértices 0 y 1.
+    fn cube_edge() -> ObjMesh {
+        let positions = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        mesh(&positions, &[0, 1, 2, 0, 2, 3, 0, 1, 4, 0, 4, 5])
+    }
+
+    #[test]
+    fn cube_has_four_vertices_and_two_triangles_per_face() {
+        let cube = ObjMesh::create_cube(2.0);
+        assert_eq!(cube.vertices.len(), 24);
+        assert_eq!(cube.indices.len(), 36);
+
+        for vertex in &cube.vertices {
+            // La normal es un eje: una componente vale ±1 y las demás cero.
+            let normal = vertex.normal;
+            assert!(close(normal.amax(), 1.0) && close(normal.abs().sum(), 1.0), "{normal:?}");
+            // Y el vértice está sobre la cara que mira en esa dirección.
+            assert!(close(vertex.position.dot(&normal), 1.0));
+        }
+    }
+
+    #[test]
+    fn torus_has_a_vertex_per_grid_point_with_unit_normals() {
+        let torus = ObjMesh::create_torus(1.65, 0.35, 12, 8);
+        assert_eq!(torus.vertices.len(), 13 * 9);
+        assert_eq!(torus.indices.len(), 12 * 8 * 6);
+
+        for vertex in &torus.vertices {
+            assert!(close(vertex.normal.magnitude(), 1.0));
+            // La normal sale del centro del tubo, que está a `major_radius` del eje.
+            let tube_center = Vec3::new(vertex.position.x, 0.0, vertex.position.z).normalize() * 1.65;
+            assert!(close((vertex.position - tube_center).dot(&vertex.normal), 0.35));
+        }
+    }
+
+    #[test]
+    fn cylinder_counts_with_and_without_caps() {
+        let open = ObjMesh::create_cylinder(1.0, 2.0, 16, false);
+        assert_eq!(open.vertices.len(), 2 * 17);
+        assert_eq!(open.indices.len(), 16 * 6);
+
+        // Cada tapa agrega su centro y un borde de `segments + 1` vértices, con un triángulo por segmento.
+        let capped = ObjMesh::create_cylinder(1.0, 2.0, 16, true);
+        assert_eq!(capped.vertices.len(), 2 * 17 + 2 * 18);
+        assert_eq!(capped.indices.len(), 16 * 6 + 2 * 16 * 3);
+    }
+
+    #[test]
+    fn cylinder_uses_at_least_three_segments() {
+        for segments in [0, 1, 2] {
+            let cylinder = ObjMesh::create_cylinder(1.0, 2.0, segments, false);
+            assert_eq!(cylinder.vertices.len(), 2 * 4);
+            assert_eq!(cylinder.indices.len(), 3 * 6);
+        }
+    }
+
+    #[test]
+    fn icosphere_quadruples_triangles_per_subdivision() {
+        for subdivisions in 0..4 {
+            let sphere = ObjMesh::create_icosphere(2.5, subdivisions);
+            assert_eq!(sphere.indices.len() / 3, 20 * 4usize.pow(subdivisions));
+            for vertex in &sphere.vertices {
+                assert!(close(vertex.position.magnitude(), 2.5));
+            }
+        }
+    }
+
+    #[test]
+    fn recompute_normals_averages_the_faces_around_each_vertex() {
+        let mut edge = cube_edge();
+        edge.recompute_normals();
+
+        let shared = Vec3::new(0.0, 1.0, 1.0).normalize();
+        let expected = [shared, shared, Vec3::y(), Vec3::y(), Vec3::z(), Vec3::z()];
+        for (vertex, expected) in edge.vertices.iter().zip(expected) {
+            assert!((vertex.normal - expected).magnitude() < 1e-5, "{:?} != {expected:?}", vertex.normal);
+        }
+    }
+
+    #[test]
+    fn make_flat_gives_each_triangle_its_own_vertices() {
+        let mut edge = cube_edge();
+        // Un triángulo con un índice inválido se omite en vez de provocar un pánico.
+        edge.indices.extend_from_slice(&[0, 1, 99]);
+        let flat = edge.make_flat();
+
+        assert_eq!(flat.vertices.len(), 12);
+        assert_eq!(flat.indices, (0..12).collect::<Vec<u32>>());
+
+        // Ningún vértice se comparte, y los tres de cada cara tienen la normal de la cara.
+        for (triangle, expected) in flat.indices.chunks_exact(3).zip([Vec3::y(), Vec3::y(), Vec3::z(), Vec3::z()]) {
+            for &index in triangle {
+                assert!((flat.vertices[index as usize].normal - expected).magnitude() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn computed_tangents_are_perpendicular_to_the_normals() {
+        let mut sphere = ObjMesh::create_sphere(1.0, 12, 16);
+        for vertex in &mut sphere.vertices {
+            vertex.tangent = Vec3::zeros();
+        }
+        sphere.compute_tangents();
+
+        for vertex in &sphere.vertices {
+            assert!(close(vertex.tangent.magnitude(), 1.0));
+            assert!(vertex.tangent.dot(&vertex.normal).abs() < 1e-4);
         }
+    }
+
+    #[test]
+    fn sphere_bounding_box_spans_its_radius() {
+        let (min, max) = ObjMesh::create_sphere(2.0, 20, 20).bounding_box();
+        for axis in 0..3 {
+            // Los polos tocan ±2 en Y; en X y Z los anillos de la esfera UV quedan apenas por dentro.
+            assert!(min[axis] <= -1.97 && min[axis] >= -2.0 - 1e-4, "{min:?}");
+            assert!(max[axis] >= 1.97 && max[axis] <= 2.0 + 1e-4, "{max:?}");
+        }
+
+        let empty = ObjMesh { vertices: Vec::new(), indices: Vec::new(), material: None };
+        assert_eq!(empty.bounding_box(), (Vec3::zeros(), Vec3::zeros()));
+    }
+
+    #[test]
+    fn center_moves_the_centroid_to_the_origin() {
+        let mut cube = ObjMesh::create_cube(2.0);
+        for vertex in &mut cube.vertices {
+            vertex.position += Vec3::new(3.0, -2.0, 5.0);
+        }
+        cube.center();
+
+        assert!(cube.centroid().magnitude() < 1e-5);
+        // El centrado solo traslada: el tamaño no cambia.
+        assert!(close(cube.vertices[0].position.amax(), 1.0));
+    }
+
+    #[test]
+    fn normalize_scale_puts_the_farthest_vertex_at_the_target() {
+        let mut torus = ObjMesh::create_torus(3.0, 1.0, 24, 12);
+        for vertex in &mut torus.vertices {
+            vertex.position += Vec3::new(1.0, 2.0, 0.0);
+        }
+        let (centroid, _) = torus.bounding_sphere();
+        torus.normalize_scale(1.5);
+
+        let (center, radius) = torus.bounding_sphere();
+        assert!(close(radius, 1.5));
+        // La escala es alrededor del centroide, así que este no se mueve.
+        assert!((center - centroid).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn load_all_from_obj_returns_one_mesh_per_model() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/two_cubes.obj");
+        let meshes = ObjMesh::load_all_from_obj(path).expect("fixture should load");
+        assert_eq!(meshes.len(), 2);
+
+        for (mesh, center_x) in meshes.iter().zip([-2.0, 2.0]) {
+            assert_eq!(mesh.vertices.len(), 8);
+            assert_eq!(mesh.indices.len(), 36);
+
+            // El archivo no trae normales: se recalculan y apuntan hacia afuera de cada cubo.
+            let center = Vec3::new(center_x, 0.0, 0.0);
+            assert!((mesh.centroid() - center).magnitude() < 1e-5);
+            for vertex in &mesh.vertices {
+                assert!(close(vertex.normal.magnitude(), 1.0));
+                assert!(vertex.normal.dot(&(vertex.position - center)) > 0.0);
+            }
+        }
+
+        let merged = ObjMesh::load_merged_from_obj(path).expect("fixture should load");
+        assert_eq!(merged.vertices.len(), 16);
+        assert!(merged.indices.iter().all(|&index| (index as usize) < merged.vertices.len()));
+    }
+
+    #[test]
+    fn save_to_obj_round_trips_through_load_from_obj() {
+        let sphere = ObjMesh::create_sphere(1.0, 8, 12);
+        let path = std::env::temp_dir().join(format!("lab4_round_trip_{}.obj", std::process::id()));
+        let path = path.to_string_lossy();
+
+        sphere.save_to_obj(&path).expect("mesh should be written");
+        let loaded = ObjMesh::load_from_obj(&path);
+        let _ = std::fs::remove_file(path.as_ref());
+        let loaded = loaded.expect("written file should parse");
+
+        assert_eq!(loaded.vertices.len(), sphere.vertices.len());
+        assert_eq!(loaded.indices.len(), sphere.indices.len());
+        // `tobj` numera los vértices en el orden en que aparecen en las caras, así que se comparan las esquinas de
+        // cada triángulo y no las listas de vértices.
+        for (&i, &j) in loaded.indices.iter().zip(&sphere.indices) {
+            let (a, b) = (&loaded.vertices[i as usize], &sphere.vertices[j as usize]);
+            assert!((a.position - b.position).magnitude() < 1e-5);
+            assert!((a.normal - b.normal).magnitude() < 1e-5);
+            assert!((a.uv - b.uv).magnitude() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn subdivide_quadruples_triangles_and_shares_midpoints() {
+        let mut cube = ObjMesh::create_cube(2.0);
+        // Un triángulo con un índice inválido se descarta en vez de provocar un pánico.
+        cube.indices.extend_from_slice(&[0, 1, 99]);
+        cube.subdivide();
+
+        assert_eq!(cube.indices.len() / 3, 12 * 4);
+        // Cada cara tiene sus propios vértices y 5 aristas (4 lados y la diagonal): un punto medio por arista.
+        assert_eq!(cube.vertices.len(), 24 + 6 * 5);
+
+        // Ningún punto medio se repite: no hay dos vértices con la misma posición y normal.
+        for (i, a) in cube.vertices.iter().enumerate() {
+            for b in &cube.vertices[i + 1..] {
+                assert!((a.position - b.position).magnitude() > 1e-5 || (a.normal - b.normal).magnitude() > 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn weld_vertices_merges_duplicates_and_keeps_indices_valid() {
+        // Dos triángulos que comparten una arista, pero el segundo usa copias de sus vértices.
+        let positions = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0 + 1e-7, 0.0),
+        ];
+        let mut quad = mesh(&positions, &[0, 1, 2, 3, 4, 5]);
+        quad.weld_vertices(1e-5);
+
+        assert_eq!(quad.vertices.len(), 4);
+        assert_eq!(quad.indices, vec![0, 1, 2, 1, 3, 2]);
+        assert!(quad.indices.iter().all(|&index| (index as usize) < quad.vertices.len()));
+    }
+
+    #[test]
+    fn bounding_sphere_matches_the_generation_radius() {
+        // La icoesfera no repite vértices en una costura, así que su centroide es el centro de la esfera.
+        for subdivisions in 0..3 {
+            let (center, radius) = ObjMesh::create_icosphere(2.5, subdivisions).bounding_sphere();
+            assert!(center.magnitude() < 1e-4, "{center:?}");
+            assert!(close(radius, 2.5), "{radius}");
+        }
+
+        let (center, radius) = ObjMesh::create_cube(2.0).bounding_sphere();
+        assert!(center.magnitude() < 1e-4);
+        assert!(close(radius, 3.0f32.sqrt()));
+    }
+
+    #[test]
+    fn plane_has_a_vertex_per_grid_point_facing_up() {
+        let plane = ObjMesh::create_plane(2.0, 3.0, 4, 6);
+        assert_eq!(plane.vertices.len(), 5 * 7);
+        assert_eq!(plane.indices.len(), 4 * 6 * 6);
+        assert!(plane.vertices.iter().all(|vertex| vertex.normal == Vec3::y()));
 
-        ObjMesh { vertices, indices }
+        // Los triángulos también miran hacia +Y, y cero subdivisiones se toman como una.
+        let single = ObjMesh::create_plane(2.0, 2.0, 0, 0);
+        assert_eq!(single.vertices.len(), 4);
+        let [a, b, c] = [0, 1, 2].map(|k| single.vertices[single.indices[k] as usize].position);
+        assert!((b - a).cross(&(c - a)).y > 0.0);
     }
 }
@@ -0,0 +1,90 @@
+// Define las órbitas de los objetos alrededor del origen de su padre en el grafo de escena.
+use nalgebra_glm::Vec3;
+
+// Órbita elíptica con el centro del padre en uno de sus focos, como una luna alrededor de su planeta. Con
+// inclinación cero el plano de la órbita es XZ y el eje mayor va sobre X; la inclinación gira ese plano
+// alrededor del eje X, así que el objeto sube y baja a lo largo de la vuelta. Con los dos semiejes iguales la
+// órbita es un círculo centrado en el padre.
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub semi_major: f32,  // Semieje mayor, sobre X.
+    pub semi_minor: f32,  // Semieje menor, sobre Z; no debe superar al mayor.
+    pub speed: f32,       // Velocidad angular en radianes por segundo; positiva gira de +X hacia +Z.
+    pub inclination: f32, // Inclinación del plano de la órbita en radianes.
+    pub phase: f32,       // Ángulo en radianes en el tiempo 0, para que los objetos no empiecen alineados.
+}
+
+impl Orbit {
+    // Crea una órbita circular de radio `radius`.
+    pub fn circular(radius: f32, speed: f32, inclination: f32, phase: f32) -> Self {
+        Orbit {
+            semi_major: radius,
+            semi_minor: radius,
+            speed,
+            inclination,
+            phase,
+        }
+    }
+
+    // Distancia del centro de la elipse al foco donde está el padre.
+    fn focal_offset(&self) -> f32 {
+        (self.semi_major * self.semi_major - self.semi_minor * self.semi_minor).max(0.0).sqrt()
+    }
+
+    // Distancia máxima al padre, en el punto más lejano de la órbita.
+    pub fn apoapsis(&self) -> f32 {
+        self.semi_major + self.focal_offset()
+    }
+}
+
+// Devuelve la posición sobre `orbit` en el tiempo `time`, relativa al padre. El ángulo avanza a velocidad
+// constante sobre la elipse (no sigue las leyes de Kepler), que basta para la animación. Con fase cero el
+// objeto empieza en el punto más lejano del padre, sobre +X.
+pub fn orbit_position(orbit: &Orbit, time: f32) -> Vec3 {
+    orbit_point(orbit, orbit.phase + time * orbit.speed)
+}
+
+// Devuelve el punto de `orbit` en el ángulo `angle`, relativo al padre. Sirve para recorrer la órbita completa
+// sin depender de su velocidad.
+pub fn orbit_point(orbit: &Orbit, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+
+    // Posición en el plano de la órbita. El centro de la elipse se desplaza hacia +X para que el padre, en el
+    // origen, quede en el foco del lado -X.
+    let x = cos * orbit.semi_major + orbit.focal_offset();
+    let z = sin * orbit.semi_minor;
+    Vec3::new(x, z * orbit.inclination.sin(), z * orbit.inclination.cos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!((actual - expected).magnitude() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn orbit_position_at_quarter_turns() {
+        // Elipse de semiejes 5 y 3: el foco está a 4 del centro, así que el padre queda a 9 del punto más lejano
+        // y a 1 del más cercano.
+        let flat = Orbit { semi_major: 5.0, semi_minor: 3.0, speed: 1.0, inclination: 0.0, phase: 0.0 };
+        assert_eq!(flat.apoapsis(), 9.0);
+        assert_close(orbit_position(&flat, 0.0), Vec3::new(9.0, 0.0, 0.0));
+        assert_close(orbit_position(&flat, FRAC_PI_2), Vec3::new(4.0, 0.0, 3.0));
+        assert_close(orbit_position(&flat, PI), Vec3::new(-1.0, 0.0, 0.0));
+
+        // La inclinación gira el plano alrededor de X: el eje mayor no cambia y el cuarto de vuelta sube.
+        let inclined = Orbit { inclination: 30f32.to_radians(), ..flat };
+        let (sin, cos) = 30f32.to_radians().sin_cos();
+        assert_close(orbit_position(&inclined, 0.0), Vec3::new(9.0, 0.0, 0.0));
+        assert_close(orbit_position(&inclined, FRAC_PI_2), Vec3::new(4.0, 3.0 * sin, 3.0 * cos));
+        assert_close(orbit_position(&inclined, PI), Vec3::new(-1.0, 0.0, 0.0));
+
+        // La fase adelanta el punto de partida y la velocidad escala el tiempo.
+        let phased = Orbit { phase: FRAC_PI_2, speed: 2.0, ..flat };
+        assert_close(orbit_position(&phased, 0.0), Vec3::new(4.0, 0.0, 3.0));
+        assert_close(orbit_position(&phased, FRAC_PI_2 / 2.0), Vec3::new(-1.0, 0.0, 0.0));
+    }
+}
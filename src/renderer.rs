@@ -1,123 +1,636 @@
 // Importa los módulos y tipos necesarios de otros archivos del proyecto y de la biblioteca nalgebra_glm.
-use crate::framebuffer::Framebuffer; // Para interactuar con el búfer de fotogramas.
+use crate::framebuffer::{plot_line, BlendMode, Color, DepthFunc, Framebuffer, FramebufferTile}; // Para interactuar con el búfer de fotogramas.
 use crate::mesh::{ObjMesh, Vertex}; // Para usar las estructuras de mallas y vértices.
-use crate::shaders::PlanetShader; // Para usar el trait de sombreado de planetas.
-use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4}; // Para operaciones matemáticas con vectores y matrices.
+use crate::shaders::{DirectionalLight, FragmentContext, Material, PlanetShader, RingOccluder, SphereOccluder, MAX_LIGHTS}; // Para usar el trait de sombreado de planetas.
+use crate::shadow::ShadowMap; // Para las sombras de la luz principal.
+use crate::texture::Texture; // Para las texturas que pueden muestrear los sombreadores.
+use nalgebra_glm::{mat4_to_mat3, Mat3, Mat4, Vec2, Vec3, Vec4}; // Para operaciones matemáticas con vectores y matrices.
+use rayon::prelude::*; // Para dibujar las franjas del búfer en paralelo.
+
+// Posiciones de las submuestras dentro de un píxel usadas para estimar la cobertura en los bordes.
+// Siguen un patrón de rejilla rotada, que detecta mejor las aristas casi horizontales o verticales.
+const COVERAGE_SAMPLES: [(f32, f32); 4] = [(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)];
+
+// Filas por defecto de cada franja del búfer. Franjas bajas reparten mejor el trabajo entre hilos y mantienen en caché
+// las filas que se dibujan; muy bajas hacen que cada triángulo grande se repita en demasiadas franjas.
+const TILE_ROWS: usize = 16;
+
+// Define el orden de los vértices (visto desde la cámara) que identifica la cara frontal de un triángulo.
+// Todas las mallas de `ObjMesh`, procedurales o cargadas de un .obj, usan `Ccw`;
+// `Cw` se usa para dibujar solo las caras traseras, como en las capas de atmósfera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    Cw,  // Sentido horario.
+    Ccw, // Sentido antihorario.
+}
+
+// Define cómo se dibujan los triángulos de una malla.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Filled,    // Triángulos rellenos y sombreados por píxel.
+    Wireframe, // Solo las aristas de cada triángulo.
+    Points,    // Solo los vértices de cada triángulo.
+}
+
+impl RenderMode {
+    // Devuelve el siguiente modo, para poder recorrerlos con una sola tecla.
+    pub fn next(self) -> Self {
+        match self {
+            RenderMode::Filled => RenderMode::Wireframe,
+            RenderMode::Wireframe => RenderMode::Points,
+            RenderMode::Points => RenderMode::Filled,
+        }
+    }
+}
+
+// Define dónde se evalúa el sombreador al rellenar triángulos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadingModel {
+    Gouraud, // Una vez por vértice; el color resultante se interpola dentro del triángulo.
+    Phong,   // Una vez por píxel, con la normal y los demás atributos interpolados.
+}
+
+impl ShadingModel {
+    // Devuelve el otro modelo, para alternarlos con una tecla.
+    pub fn toggled(self) -> Self {
+        match self {
+            ShadingModel::Gouraud => ShadingModel::Phong,
+            ShadingModel::Phong => ShadingModel::Gouraud,
+        }
+    }
+}
+
+// Define qué muestran los triángulos rellenos: el color del sombreador o un dato de depuración.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugView {
+    None,     // El color del sombreador.
+    Depth,    // Profundidad lineal en escala de grises: blanco en el plano cercano y negro en el lejano.
+    Normals,  // Normal interpolada en el espacio del mundo, de [-1, 1] a RGB.
+    Uv,       // Coordenadas de textura como (u, v, 0); fuera de [0, 1] se repiten y se marcan en azul.
+    Overdraw, // Veces que se escribió cada píxel, como mapa de calor; se aplica con `Framebuffer::overdraw_heatmap`.
+}
+
+impl DebugView {
+    // Devuelve la siguiente vista, para poder recorrerlas con una sola tecla.
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::None => DebugView::Depth,
+            DebugView::Depth => DebugView::Normals,
+            DebugView::Normals => DebugView::Uv,
+            DebugView::Uv => DebugView::Overdraw,
+            DebugView::Overdraw => DebugView::None,
+        }
+    }
+
+    // Indica si la vista reemplaza el color del sombreador. La de sobredibujo no lo hace: necesita que los
+    // fragmentos translúcidos se mezclen como siempre para contar las mismas escrituras.
+    fn replaces_shader(self) -> bool {
+        matches!(self, DebugView::Depth | DebugView::Normals | DebugView::Uv)
+    }
+}
+
+// Lo que cambia de un objeto a otro al dibujar: la malla, cómo se sombrea, dónde está y en qué instante.
+pub struct DrawCall<'a> {
+    pub mesh: &'a ObjMesh,             // Malla en el espacio del objeto.
+    pub shader: &'a dyn PlanetShader,  // Sombreador que da color a cada fragmento.
+    pub material: Material,            // Coeficientes de iluminación que recibe el sombreador.
+    pub model_matrix: Mat4,            // Del espacio del objeto al mundo.
+    pub time: f32,                     // Tiempo de la animación en segundos.
+}
+
+// Contadores de lo que hizo el renderizador al dibujar una o más mallas. Se pueden sumar con `+=`
+// para obtener los totales de un cuadro.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    pub submitted: usize,      // Triángulos recibidos con índices válidos.
+    pub drawn: usize,          // Triángulos que pasaron el culling, tocan la pantalla y se enviaron a dibujar.
+    pub culled: usize,         // Triángulos descartados por mirar en dirección contraria a la cámara.
+    pub clipped: usize,        // Triángulos recortados o descartados por el plano cercano.
+    pub pixels_written: usize, // Píxeles que pasaron la prueba de profundidad y se escribieron.
+}
+
+impl std::ops::AddAssign for RenderStats {
+    fn add_assign(&mut self, other: RenderStats) {
+        self.submitted += other.submitted;
+        self.drawn += other.drawn;
+        self.culled += other.culled;
+        self.clipped += other.clipped;
+        self.pixels_written += other.pixels_written;
+    }
+}
 
 // Define el renderizador, que se encarga de dibujar las mallas en el búfer de fotogramas.
 pub struct Renderer {
-    pub width: f32, // Ancho de la pantalla.
-    pub height: f32, // Alto de la pantalla.
+    pub width: f32, // Ancho del búfer interno (ancho de la pantalla por el factor de supermuestreo).
+    pub height: f32, // Alto del búfer interno (alto de la pantalla por el factor de supermuestreo).
+    pub supersample: usize, // Factor de supermuestreo (SSAA); 1 lo desactiva.
+    pub edge_antialiasing: bool, // Si es verdadero, suaviza los bordes mezclando según la cobertura de cada píxel.
+    pub parallel: bool, // Si es verdadero, dibuja en varios hilos; falso da un camino de un solo hilo.
+    pub tile_rows: usize, // Filas de cada franja; con la altura del búfer hay una sola franja con todos los triángulos.
+    pub cull_backfaces: bool, // Si es verdadero, descarta los triángulos que miran en dirección contraria a la cámara.
+    pub depth_buffer: Vec<f32>, // Profundidad de cada píxel del búfer interno; infinito donde no hay geometría.
+    pub depth_func: DepthFunc, // Función de comparación usada en la prueba de profundidad.
+    pub depth_write: bool, // Si es falso, ni los fragmentos opacos escriben profundidad (objetos translúcidos).
+    pub blend_mode: BlendMode, // Cómo se combinan los fragmentos translúcidos y los bordes con lo ya dibujado.
+    pub front_face: Winding, // Orden de vértices que se considera cara frontal.
+    pub render_mode: RenderMode, // Modo de dibujo de los triángulos.
+    pub shading_model: ShadingModel, // Si el sombreador se evalúa por vértice o por píxel en el modo relleno.
+    pub debug_view: DebugView, // Dato de depuración que reemplaza al sombreador, si hay alguno.
+    pub depth_range: (f32, f32), // Planos cercano y lejano de la proyección, para la vista de profundidad.
+    pub lights: Vec<DirectionalLight>, // Luces de la escena. Solo se usan las primeras `MAX_LIGHTS`.
+    pub occluders: Vec<SphereOccluder>, // Esferas que eclipsan a los demás objetos; se actualizan cada cuadro.
+    pub rings: Vec<RingOccluder>, // Anillos que dan sombra a los demás objetos; se actualizan cada cuadro.
+    pub shadow_map: Option<ShadowMap>, // Profundidad vista desde la primera luz; `None` usa solo las sombras analíticas.
+    pub textures: Vec<Texture>, // Texturas disponibles para los sombreadores, elegidas por índice.
+    material: Material, // Material del objeto que se dibuja; `render_mesh` lo toma de cada `DrawCall`.
+    pub camera_position: Vec3, // Posición de la cámara en el mundo; debe actualizarse cada cuadro.
+    pub background: [Color; 2], // Degradado del fondo, de arriba a abajo, con que se limpia el búfer en cada escena.
+    body_frame: Mat4, // Marco del cuerpo que se dibuja, en el que los sombreadores calculan sus patrones.
+    body_rotation: Mat3, // Rotación de `body_frame`, sin escala, para llevar direcciones de ese marco al mundo.
 }
 
 impl Renderer {
-    // Crea una nueva instancia del renderizador.
-    pub fn new(width: usize, height: usize) -> Self {
+    // Crea una nueva instancia del renderizador para una pantalla de `width`×`height`.
+    // Con `supersample` mayor que 1 se dibuja a esa resolución multiplicada y el búfer se reduce después
+    // con `Framebuffer::downsample_into`; el búfer interno debe crearse con `internal_size`.
+    pub fn new(width: usize, height: usize, supersample: usize) -> Self {
+        let supersample = supersample.max(1);
         Renderer {
-            width: width as f32,
-            height: height as f32,
+            width: (width * supersample) as f32,
+            height: (height * supersample) as f32,
+            supersample,
+            edge_antialiasing: true,
+            parallel: true,
+            tile_rows: TILE_ROWS,
+            cull_backfaces: true, // El culling está activado por defecto.
+            depth_buffer: vec![f32::INFINITY; width * height * supersample * supersample],
+            depth_func: DepthFunc::Less, // Por defecto gana el fragmento más cercano.
+            depth_write: true,
+            blend_mode: BlendMode::Alpha,
+            front_face: Winding::Ccw, // Convención estándar de OpenGL.
+            render_mode: RenderMode::Filled,
+            shading_model: ShadingModel::Phong,
+            debug_view: DebugView::None,
+            depth_range: (0.1, 100.0),
+            // Una sola luz blanca en la posición histórica que usaban la mayoría de los sombreadores.
+            lights: vec![DirectionalLight::white(Vec3::new(1.0, 0.5, 1.0))],
+            occluders: Vec::new(),
+            rings: Vec::new(),
+            shadow_map: None,
+            textures: Vec::new(),
+            material: Material::default(),
+            camera_position: Vec3::zeros(),
+            background: [Color::BLACK; 2],
+            body_frame: Mat4::identity(),
+            body_rotation: Mat3::identity(),
         }
     }
 
-    // Renderiza una malla en el búfer de fotogramas usando un sombreador específico.
-    pub fn render_mesh(
+    // Cambia el marco del cuerpo de los próximos objetos: su matriz de modelo sin el giro animado (posición,
+    // inclinación del eje y escala uniforme). Los sombreadores reciben la posición de cada fragmento en ese marco,
+    // así que sus patrones se quedan centrados en el cuerpo y siguen su inclinación. Con la identidad, que es el
+    // valor inicial, el marco es el del mundo.
+    pub fn set_body_frame(&mut self, frame: &Mat4) {
+        self.body_frame = *frame;
+        let upper = mat4_to_mat3(frame);
+        self.body_rotation = Mat3::from_columns(&[
+            upper.column(0).normalize(),
+            upper.column(1).normalize(),
+            upper.column(2).normalize(),
+        ]);
+    }
+
+    // Actualiza la resolución de la pantalla, conservando el factor de supermuestreo. El búfer de profundidad
+    // cambia con ella y queda vacío; el de fotogramas debe redimensionarse después con el nuevo `internal_size`.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = (width * self.supersample) as f32;
+        self.height = (height * self.supersample) as f32;
+        let (width, height) = self.internal_size();
+        self.depth_buffer.resize(width * height, f32::INFINITY);
+        self.clear_depth();
+    }
+
+    // Restablece el búfer de profundidad a infinito. Debe llamarse en cada cuadro junto con la limpieza del
+    // búfer de fotogramas.
+    #[inline]
+    pub fn clear_depth(&mut self) {
+        self.depth_buffer.fill(f32::INFINITY);
+    }
+
+    // Devuelve el tamaño en píxeles del búfer interno en el que dibuja el renderizador.
+    pub fn internal_size(&self) -> (usize, usize) {
+        (self.width as usize, self.height as usize)
+    }
+
+    // Construye el contexto que reciben los sombreadores para un fragmento.
+    #[inline]
+    fn fragment_context(
         &self,
+        world_pos: Vec3,
+        local_pos: Vec3,
+        body_pos: Vec3,
+        world_normal: Vec3,
+        uv: Vec2,
+        time: f32,
+    ) -> FragmentContext<'_> {
+        FragmentContext {
+            world_position: world_pos,
+            local_position: local_pos,
+            body_position: body_pos,
+            body_to_world: self.body_rotation,
+            normal: world_normal,
+            uv,
+            view_dir: (self.camera_position - world_pos).normalize(),
+            time,
+            lights: &self.lights[..self.lights.len().min(MAX_LIGHTS)],
+            occluders: &self.occluders,
+            rings: &self.rings,
+            shadow: self.shadow_map.as_ref().map(|map| map.visibility(&world_pos, &world_normal)),
+            textures: &self.textures,
+            material: self.material,
+        }
+    }
+
+    // Llama al sombreador para obtener el color de un vértice, ignorando su opacidad.
+    #[inline]
+    fn shade(&self, shader: &dyn PlanetShader, v: &TransformedVertex, time: f32) -> Color {
+        if self.debug_view.replaces_shader() {
+            return self.debug_color(v.view_depth, &v.world_normal, v.uv);
+        }
+        shader.fragment(&self.fragment_context(v.world_pos, v.local_pos, v.body_pos, v.world_normal, v.uv, time))
+    }
+
+    // Devuelve el color de la vista de depuración activa para un fragmento. `view_depth` es la distancia al plano
+    // de la cámara, `normal` la normal en el espacio del mundo y `uv` las coordenadas de textura.
+    // Con las vistas que no reemplazan al sombreador devuelve negro.
+    #[inline]
+    fn debug_color(&self, view_depth: f32, normal: &Vec3, uv: Vec2) -> Color {
+        match self.debug_view {
+            DebugView::None | DebugView::Overdraw => Color::BLACK,
+            DebugView::Depth => {
+                let (near, far) = self.depth_range;
+                let gray = ((far - view_depth) / (far - near)).clamp(0.0, 1.0);
+                Color::from_vec3(Vec3::repeat(gray))
+            }
+            DebugView::Normals => Color::from_vec3(normal * 0.5 + Vec3::repeat(0.5)),
+            DebugView::Uv => {
+                let outside = !(0.0..=1.0).contains(&uv.x) || !(0.0..=1.0).contains(&uv.y);
+                let (u, v) = if outside { (uv.x.rem_euclid(1.0), uv.y.rem_euclid(1.0)) } else { (uv.x, uv.y) };
+                Color::from_vec3(Vec3::new(u, v, if outside { 1.0 } else { 0.0 }))
+            }
+        }
+    }
+
+    // Renderiza la malla de `draw` en el búfer de fotogramas, vista con `view_matrix` y `projection_matrix`.
+    // Devuelve las estadísticas de los triángulos y píxeles procesados. Los triángulos que cruzan el plano
+    // cercano se recortan antes de dibujarse, y `drawn` y `culled` cuentan las piezas que resultan del recorte.
+    pub fn render_mesh(
+        &mut self,
         framebuffer: &mut Framebuffer,
-        mesh: &ObjMesh,
-        shader: &dyn PlanetShader,
-        model_matrix: &Mat4,
+        draw: &DrawCall,
         view_matrix: &Mat4,
         projection_matrix: &Mat4,
-        time: f32,
-    ) {
+    ) -> RenderStats {
+        let mut stats = RenderStats::default();
+        let (mesh, shader, model_matrix, time) = (draw.mesh, draw.shader, &draw.model_matrix, draw.time);
+        self.material = draw.material;
+
         // Calcula la matriz Modelo-Vista-Proyección (MVP) para transformar los vértices.
         let mvp = projection_matrix * view_matrix * model_matrix;
 
+        // La matriz de normales es la inversa transpuesta de la parte 3x3 de la matriz de modelo,
+        // así las normales siguen siendo perpendiculares a la superficie con escalas no uniformes.
+        let normal_matrix = normal_matrix(model_matrix);
+
+        // Lleva los vértices del espacio del objeto al marco del cuerpo; si el marco no se puede invertir
+        // (escala cero), se usa el mundo.
+        let local_to_body = self.body_frame.try_inverse().map_or(*model_matrix, |inverse| inverse * model_matrix);
+
         // Transforma cada vértice de la malla del espacio del objeto al espacio de la pantalla.
-        let transformed_vertices: Vec<_> = mesh
+        let mut vertices: Vec<_> = mesh
             .vertices
             .iter()
-            .map(|v| self.transform_vertex(v, model_matrix, &mvp))
+            .map(|v| self.transform_vertex(v, model_matrix, view_matrix, &normal_matrix, &local_to_body, &mvp))
             .collect();
 
-        // Itera sobre los índices de la malla para procesar cada triángulo.
+        // Primera fase: recorta y descarta triángulos, y reparte los que hay que dibujar entre las franjas del búfer
+        // que tocan (binning), en orden y como índices en `vertices`. Los vértices que crea el recorte se agregan
+        // al final de la lista.
+        let mut bins: Vec<Vec<[usize; 3]>> = vec![Vec::new(); framebuffer.height.div_ceil(self.tile_rows.max(1))];
         for i in (0..mesh.indices.len()).step_by(3) {
             let i0 = mesh.indices[i] as usize;
             let i1 = mesh.indices[i + 1] as usize;
             let i2 = mesh.indices[i + 2] as usize;
 
             // Se asegura de que los índices sean válidos.
-            if i0 < transformed_vertices.len()
-                && i1 < transformed_vertices.len()
-                && i2 < transformed_vertices.len()
-            {
+            if i0 < mesh.vertices.len() && i1 < mesh.vertices.len() && i2 < mesh.vertices.len() {
+                stats.submitted += 1;
+
+                // Los triángulos completamente delante del plano cercano se usan tal cual.
+                if [i0, i1, i2].iter().all(|&index| vertices[index].near_distance() >= 0.0) {
+                    self.bin_triangle(&vertices, [i0, i1, i2], &mut bins, &mut stats);
+                    continue;
+                }
+
+                // Los demás se recortan; el polígono resultante (vacío, triángulo o cuadrilátero) se divide en abanico.
+                stats.clipped += 1;
+                let polygon = self.clip_near([&vertices[i0], &vertices[i1], &vertices[i2]]);
+                let first = vertices.len();
+                vertices.extend(polygon);
+                for k in first + 1..vertices.len().saturating_sub(1) {
+                    self.bin_triangle(&vertices, [first, k, k + 1], &mut bins, &mut stats);
+                }
+            }
+        }
+
+        // Con sombreado Gouraud el sombreador se evalúa una sola vez por vértice, incluidos los que creó el recorte,
+        // y al rellenar solo se interpolan los colores resultantes.
+        if self.render_mode == RenderMode::Filled && self.shading_model == ShadingModel::Gouraud {
+            let shade_vertex = |v: &mut TransformedVertex| {
+                let ctx = self.fragment_context(v.world_pos, v.local_pos, v.body_pos, v.world_normal, v.uv, time);
+                let (color, alpha) = shader.fragment_with_alpha(&ctx);
+                v.color = color.to_vec3();
+                v.alpha = alpha;
+            };
+            if self.parallel {
+                vertices.par_iter_mut().for_each(shade_vertex);
+            } else {
+                vertices.iter_mut().for_each(shade_vertex);
+            }
+        }
+
+        // Segunda fase: cada franja dibuja solo los triángulos de su bin. Las franjas no comparten píxeles y cada
+        // una respeta el orden de los triángulos, así que el resultado en paralelo es idéntico al de un solo hilo.
+        // Las franjas toman prestado el búfer de profundidad mientras el resto del renderizador se comparte entre
+        // los hilos, así que se saca de `self` durante el dibujo. Si el búfer de fotogramas no tiene el tamaño
+        // del de profundidad, este se ajusta vacío.
+        let mut depth_buffer = std::mem::take(&mut self.depth_buffer);
+        if depth_buffer.len() != framebuffer.width * framebuffer.height {
+            depth_buffer = vec![f32::INFINITY; framebuffer.width * framebuffer.height];
+        }
+        let tiles = framebuffer.tiles(self.tile_rows.max(1), &mut depth_buffer, self.depth_func);
+        stats.pixels_written = if self.parallel {
+            tiles
+                .into_par_iter()
+                .zip(bins.par_iter())
+                .map(|(mut tile, bin)| self.draw_triangles(&mut tile, &vertices, bin, shader, time))
+                .sum()
+        } else {
+            tiles
+                .into_iter()
+                .zip(&bins)
+                .map(|(mut tile, bin)| self.draw_triangles(&mut tile, &vertices, bin, shader, time))
+                .sum()
+        };
+        self.depth_buffer = depth_buffer;
+
+        stats
+    }
+
+    // Aplica el culling a un triángulo ya proyectado y, si no se descarta, lo agrega al bin de cada franja
+    // que cruza su cuadro delimitador. Los triángulos que quedan fuera de la pantalla no se agregan a ninguno.
+    fn bin_triangle(
+        &self,
+        vertices: &[TransformedVertex],
+        triangle: [usize; 3],
+        bins: &mut [Vec<[usize; 3]>],
+        stats: &mut RenderStats,
+    ) {
+        let [v0, v1, v2] = triangle.map(|index| &vertices[index]);
+
+        // Omite los triángulos que miran en dirección contraria a la cámara.
+        if self.cull_backfaces && self.is_back_facing(v0, v1, v2) {
+            stats.culled += 1;
+            return;
+        }
+
+        // Cuadro delimitador en píxeles, con el mismo redondeo que usa el rasterizador.
+        let min_x = v0.screen_pos.x.min(v1.screen_pos.x).min(v2.screen_pos.x).floor();
+        let max_x = v0.screen_pos.x.max(v1.screen_pos.x).max(v2.screen_pos.x).ceil();
+        let min_y = v0.screen_pos.y.min(v1.screen_pos.y).min(v2.screen_pos.y).floor();
+        let max_y = v0.screen_pos.y.max(v1.screen_pos.y).max(v2.screen_pos.y).ceil();
+        if bins.is_empty() || max_x < 0.0 || max_y < 0.0 || min_x >= self.width || min_y >= self.height {
+            return;
+        }
+
+        let rows = self.tile_rows.max(1);
+        let first = min_y.max(0.0) as usize / rows;
+        let last = (max_y as usize / rows).min(bins.len() - 1);
+        for bin in &mut bins[first..=last] {
+            bin.push(triangle);
+        }
+        stats.drawn += 1;
+    }
+
+    // Dibuja en una franja del búfer los triángulos de su bin, según el modo de dibujo.
+    // Devuelve la cantidad de píxeles escritos.
+    fn draw_triangles(
+        &self,
+        tile: &mut FramebufferTile,
+        vertices: &[TransformedVertex],
+        triangles: &[[usize; 3]],
+        shader: &dyn PlanetShader,
+        time: f32,
+    ) -> usize {
+        let mut written = 0;
+
+        for triangle in triangles {
+            let [v0, v1, v2] = triangle.map(|index| &vertices[index]);
+            written += match self.render_mode {
                 // Rasteriza el triángulo formado por los tres vértices.
-                self.rasterize_triangle(
-                    framebuffer,
-                    &transformed_vertices[i0],
-                    &transformed_vertices[i1],
-                    &transformed_vertices[i2],
-                    shader,
-                    time,
-                );
+                RenderMode::Filled => self.rasterize_triangle(tile, v0, v1, v2, shader, time),
+                RenderMode::Wireframe => self.draw_triangle_edges(tile, v0, v1, v2, shader, time),
+                RenderMode::Points => self.draw_triangle_points(tile, v0, v1, v2, shader, time),
+            };
+        }
+
+        written
+    }
+
+    // Recorta un triángulo contra el plano cercano en el espacio de recorte (Sutherland-Hodgman con un solo plano).
+    // Devuelve los vértices del polígono que queda delante, en el mismo orden de vueltas: ninguno si el triángulo
+    // está completamente detrás, 3 si se corta un vértice y 4 si se cortan dos. Los vértices nuevos interpolan
+    // todos los atributos sobre la arista que cruza el plano.
+    fn clip_near(&self, vertices: [&TransformedVertex; 3]) -> Vec<TransformedVertex> {
+        let mut polygon = Vec::with_capacity(4);
+
+        for i in 0..3 {
+            let (a, b) = (vertices[i], vertices[(i + 1) % 3]);
+            let (da, db) = (a.near_distance(), b.near_distance());
+
+            if da >= 0.0 {
+                polygon.push(*a);
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                let t = da / (da - db);
+                polygon.push(self.project(TransformedVertex::lerp(a, b, t)));
+            }
+        }
+
+        polygon
+    }
+
+    // Dibuja las tres aristas de un triángulo, usando el color del sombreador en cada vértice. El triángulo ya
+    // viene recortado contra el plano cercano; las partes detrás del plano lejano se descartan píxel por píxel.
+    // Devuelve la cantidad de píxeles escritos.
+    fn draw_triangle_edges(
+        &self,
+        tile: &mut FramebufferTile,
+        v0: &TransformedVertex,
+        v1: &TransformedVertex,
+        v2: &TransformedVertex,
+        shader: &dyn PlanetShader,
+        time: f32,
+    ) -> usize {
+        let c0 = self.shade(shader, v0, time).to_vec3();
+        let c1 = self.shade(shader, v1, time).to_vec3();
+        let c2 = self.shade(shader, v2, time).to_vec3();
+
+        self.draw_line(tile, v0, v1, c0, c1)
+            + self.draw_line(tile, v1, v2, c1, c2)
+            + self.draw_line(tile, v2, v0, c2, c0)
+    }
+
+    // Dibuja los tres vértices de un triángulo como píxeles individuales.
+    // Devuelve la cantidad de píxeles escritos.
+    fn draw_triangle_points(
+        &self,
+        tile: &mut FramebufferTile,
+        v0: &TransformedVertex,
+        v1: &TransformedVertex,
+        v2: &TransformedVertex,
+        shader: &dyn PlanetShader,
+        time: f32,
+    ) -> usize {
+        let mut written = 0;
+        for v in [v0, v1, v2] {
+            if !v.in_depth_range() || v.screen_pos.x < 0.0 || v.screen_pos.y < 0.0 {
+                continue;
+            }
+
+            let color = self.shade(shader, v, time);
+            if tile.set_pixel(v.screen_pos.x as usize, v.screen_pos.y as usize, color, v.depth) {
+                written += 1;
             }
         }
+        written
+    }
+
+    // Dibuja una línea entre dos vértices con `plot_line`, recortada a las filas de la franja, interpolando la
+    // profundidad y el color a lo largo de la línea. Los píxeles fuera del rango de profundidad se descartan.
+    // Devuelve la cantidad de píxeles escritos.
+    fn draw_line(&self, tile: &mut FramebufferTile, a: &TransformedVertex, b: &TransformedVertex, color_a: Vec3, color_b: Vec3) -> usize {
+        let (x0, y0) = (a.screen_pos.x.floor() as i32, a.screen_pos.y.floor() as i32);
+        let (x1, y1) = (b.screen_pos.x.floor() as i32, b.screen_pos.y.floor() as i32);
+        let min = (0, tile.y_start as i32);
+        let max = (tile.width as i32 - 1, tile.y_end as i32 - 1);
+
+        let mut written = 0;
+        plot_line(x0, y0, x1, y1, min, max, |x, y, t| {
+            let depth = a.depth + (b.depth - a.depth) * t;
+            if !(-1.0..=1.0).contains(&depth) {
+                return;
+            }
+
+            let color = Color::from_vec3(color_a + (color_b - color_a) * t);
+            if tile.set_pixel(x, y, color, depth) {
+                written += 1;
+            }
+        });
+        written
+    }
+
+    // Determina si un triángulo proyectado mira en dirección contraria a la cámara según su orden de vértices.
+    fn is_back_facing(&self, v0: &TransformedVertex, v1: &TransformedVertex, v2: &TransformedVertex) -> bool {
+        // Área con signo en el espacio de pantalla. Como el eje Y de la pantalla apunta hacia abajo,
+        // un triángulo antihorario visto por la cámara tiene área negativa.
+        let a = v1.screen_pos - v0.screen_pos;
+        let b = v2.screen_pos - v0.screen_pos;
+        let signed_area = a.x * b.y - a.y * b.x;
+
+        match self.front_face {
+            Winding::Ccw => signed_area >= 0.0,
+            Winding::Cw => signed_area <= 0.0,
+        }
     }
 
     // Transforma un solo vértice del espacio del modelo al espacio de la pantalla.
-    fn transform_vertex(&self, vertex: &Vertex, model_matrix: &Mat4, mvp: &Mat4) -> TransformedVertex {
+    fn transform_vertex(
+        &self,
+        vertex: &Vertex,
+        model_matrix: &Mat4,
+        view_matrix: &Mat4,
+        normal_matrix: &Mat3,
+        local_to_body: &Mat4,
+        mvp: &Mat4,
+    ) -> TransformedVertex {
         let pos4 = Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
 
         // Calcula la posición y la normal en el espacio del mundo.
         let world_pos = model_matrix * pos4;
-        let normal4 = Vec4::new(vertex.normal.x, vertex.normal.y, vertex.normal.z, 0.0);
-        let world_normal = (model_matrix * normal4).xyz().normalize();
+        let world_normal = (normal_matrix * vertex.normal).normalize();
 
-        // Proyecta el vértice al espacio de recorte (clip space).
-        let clip_pos = mvp * pos4;
+        // Proyecta el vértice al espacio de recorte (clip space); los campos de pantalla se calculan en `project`.
+        self.project(TransformedVertex {
+            clip_pos: mvp * pos4,
+            screen_pos: Vec2::zeros(),
+            depth: 0.0,
+            inv_w: 0.0,
+            world_pos: world_pos.xyz(),
+            local_pos: vertex.position,
+            body_pos: (local_to_body * pos4).xyz(),
+            view_depth: -(view_matrix * world_pos).z, // La cámara mira hacia -Z en el espacio de la vista.
+            world_normal,
+            uv: vertex.uv,
+            color: Vec3::zeros(),
+            alpha: 1.0,
+        })
+    }
 
+    // Calcula la posición en pantalla, la profundidad y 1/W de un vértice a partir de su posición de recorte.
+    fn project(&self, mut vertex: TransformedVertex) -> TransformedVertex {
         // Realiza la división de perspectiva para obtener las coordenadas normalizadas del dispositivo (NDC).
-        let w = clip_pos.w;
+        // Con una proyección ortográfica `w` vale 1, así que la división deja las coordenadas intactas.
+        let w = vertex.clip_pos.w;
         if w.abs() < 1e-6 {
-            // Evita la división por cero y descarta vértices problemáticos.
-            return TransformedVertex {
-                screen_pos: Vec2::new(-1000.0, -1000.0),
-                depth: 1.0,
-                world_pos: world_pos.xyz(),
-                world_normal,
-            };
+            // Evita la división por cero. Estos vértices quedan detrás del plano cercano, así que sus
+            // triángulos se recortan y nunca se usan estos valores.
+            vertex.screen_pos = Vec2::new(-1000.0, -1000.0);
+            vertex.depth = 1.0;
+            vertex.inv_w = 0.0;
+            return vertex;
         }
-        let ndc = clip_pos.xyz() / w;
+        let ndc = vertex.clip_pos.xyz() / w;
 
         // Convierte las coordenadas NDC al espacio de la pantalla.
-        let screen = Vec2::new(
+        vertex.screen_pos = Vec2::new(
             (ndc.x + 1.0) * 0.5 * self.width,
             (1.0 - ndc.y) * 0.5 * self.height, // Se invierte la coordenada Y.
         );
-
-        TransformedVertex {
-            screen_pos: screen,
-            depth: ndc.z,
-            world_pos: world_pos.xyz(),
-            world_normal,
-        }
+        vertex.depth = ndc.z;
+        vertex.inv_w = 1.0 / w;
+        vertex
     }
 
     // Rasteriza un triángulo, dibujando los píxeles que lo componen en el búfer de fotogramas.
+    // Devuelve la cantidad de píxeles escritos.
     fn rasterize_triangle(
         &self,
-        framebuffer: &mut Framebuffer,
+        tile: &mut FramebufferTile,
         v0: &TransformedVertex,
         v1: &TransformedVertex,
         v2: &TransformedVertex,
         shader: &dyn PlanetShader,
         time: f32,
-    ) {
-        // Calcula el cuadro delimitador (bounding box) del triángulo para optimizar el recorrido de píxeles.
+    ) -> usize {
+        let mut written = 0;
+
+        // Calcula el cuadro delimitador (bounding box) del triángulo para optimizar el recorrido de píxeles,
+        // limitado a las filas de la franja.
         let min_x = v0.screen_pos.x.min(v1.screen_pos.x).min(v2.screen_pos.x).floor().max(0.0) as usize;
         let max_x = v0.screen_pos.x.max(v1.screen_pos.x).max(v2.screen_pos.x).ceil().min(self.width - 1.0) as usize;
-        let min_y = v0.screen_pos.y.min(v1.screen_pos.y).min(v2.screen_pos.y).floor().max(0.0) as usize;
-        let max_y = v0.screen_pos.y.max(v1.screen_pos.y).max(v2.screen_pos.y).ceil().min(self.height - 1.0) as usize;
+        let min_y = v0.screen_pos.y.min(v1.screen_pos.y).min(v2.screen_pos.y).floor().max(tile.y_start as f32) as usize;
+        let max_y = v0.screen_pos.y.max(v1.screen_pos.y).max(v2.screen_pos.y).ceil().min(tile.y_end as f32 - 1.0) as usize;
 
         // Itera sobre cada píxel dentro del cuadro delimitador.
         for y in min_y..=max_y {
@@ -127,30 +640,185 @@ impl Renderer {
                 // Calcula las coordenadas baricéntricas del píxel actual.
                 let (w0, w1, w2) = barycentric(&p, &v0.screen_pos, &v1.screen_pos, &v2.screen_pos);
 
-                // Si el píxel está dentro del triángulo, lo procesa.
+                // Si el centro del píxel está dentro del triángulo, el píxel le pertenece por completo.
                 if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
-                    // Interpola la profundidad, la posición en el mundo y la normal del vértice.
-                    let depth = w0 * v0.depth + w1 * v1.depth + w2 * v2.depth;
-                    let world_pos = v0.world_pos * w0 + v1.world_pos * w1 + v2.world_pos * w2;
-                    let world_normal = (v0.world_normal * w0 + v1.world_normal * w1 + v2.world_normal * w2).normalize();
+                    if let Some((color, alpha, depth)) = self.shade_fragment((w0, w1, w2), v0, v1, v2, shader, time) {
+                        // Dibuja el píxel en el búfer de fotogramas, realizando la prueba de profundidad.
+                        // Los fragmentos translúcidos, y todos los de un objeto sin escritura de profundidad,
+                        // se mezclan sin escribirla.
+                        let drawn = if alpha >= 1.0 && self.depth_write {
+                            tile.set_pixel(x, y, color, depth)
+                        } else {
+                            alpha > 0.0 && tile.blend_pixel(x, y, color, alpha, depth, self.blend_mode)
+                        };
+                        if drawn {
+                            written += 1;
+                        }
+                    }
+                    continue;
+                }
+
+                // Si el centro queda fuera pero algunas submuestras caen dentro, el píxel está en un borde:
+                // se mezcla con lo que ya hay según la fracción cubierta. En las aristas compartidas el píxel
+                // pertenece al triángulo vecino, así que la mezcla queda entre ambos y no deja ver el fondo.
+                if !self.edge_antialiasing {
+                    continue;
+                }
 
-                    // Llama al sombreador de fragmentos para obtener el color del píxel.
-                    let color = shader.fragment(&world_pos, &world_normal, time);
+                let mut covered = 0;
+                let mut covered_sum = Vec2::zeros();
+                for offset in COVERAGE_SAMPLES {
+                    let sample = Vec2::new(x as f32 + offset.0, y as f32 + offset.1);
+                    let (s0, s1, s2) = barycentric(&sample, &v0.screen_pos, &v1.screen_pos, &v2.screen_pos);
+                    if s0 >= 0.0 && s1 >= 0.0 && s2 >= 0.0 {
+                        covered += 1;
+                        covered_sum += sample;
+                    }
+                }
+
+                if covered == 0 {
+                    continue;
+                }
 
-                    // Dibuja el píxel en el búfer de fotogramas, realizando la prueba de profundidad.
-                    framebuffer.set_pixel(x, y, color, depth);
+                // Se sombrea en el promedio de las submuestras cubiertas, que siempre está dentro del triángulo.
+                let center = covered_sum / covered as f32;
+                let weights = barycentric(&center, &v0.screen_pos, &v1.screen_pos, &v2.screen_pos);
+                if let Some((color, alpha, depth)) = self.shade_fragment(weights, v0, v1, v2, shader, time) {
+                    let coverage = covered as f32 / COVERAGE_SAMPLES.len() as f32;
+                    if tile.blend_pixel(x, y, color, coverage * alpha, depth, self.blend_mode) {
+                        written += 1;
+                    }
                 }
             }
         }
+
+        written
+    }
+
+    // Interpola los atributos de un fragmento a partir de sus coordenadas baricéntricas y lo sombrea.
+    // Devuelve el color, la opacidad y la profundidad, o `None` si el fragmento queda fuera del rango de profundidad.
+    #[inline]
+    fn shade_fragment(
+        &self,
+        (w0, w1, w2): (f32, f32, f32),
+        v0: &TransformedVertex,
+        v1: &TransformedVertex,
+        v2: &TransformedVertex,
+        shader: &dyn PlanetShader,
+        time: f32,
+    ) -> Option<(Color, f32, f32)> {
+        // Interpola la profundidad (z en NDC, que es lineal en el espacio de pantalla).
+        let depth = w0 * v0.depth + w1 * v1.depth + w2 * v2.depth;
+
+        // Descarta los fragmentos fuera del rango [-1, 1] (delante del plano cercano o detrás del lejano).
+        if !(-1.0..=1.0).contains(&depth) {
+            return None;
+        }
+
+        // Interpola las posiciones, la normal y las coordenadas UV del vértice con corrección de perspectiva.
+        let (p0, p1, p2) = perspective_correct((w0, w1, w2), v0.inv_w, v1.inv_w, v2.inv_w);
+
+        // Las vistas de depuración reemplazan al sombreador y siempre son opacas.
+        if self.debug_view.replaces_shader() {
+            let view_depth = v0.view_depth * p0 + v1.view_depth * p1 + v2.view_depth * p2;
+            let normal = (v0.world_normal * p0 + v1.world_normal * p1 + v2.world_normal * p2).normalize();
+            let uv = v0.uv * p0 + v1.uv * p1 + v2.uv * p2;
+            return Some((self.debug_color(view_depth, &normal, uv), 1.0, depth));
+        }
+
+        // Con sombreado Gouraud basta con interpolar el color y la opacidad que ya se calcularon en los vértices.
+        if self.shading_model == ShadingModel::Gouraud {
+            let color = v0.color * p0 + v1.color * p1 + v2.color * p2;
+            let alpha = v0.alpha * p0 + v1.alpha * p1 + v2.alpha * p2;
+            return Some((Color::from_vec3(color), alpha, depth));
+        }
+
+        let world_pos = v0.world_pos * p0 + v1.world_pos * p1 + v2.world_pos * p2;
+        let local_pos = v0.local_pos * p0 + v1.local_pos * p1 + v2.local_pos * p2;
+        let body_pos = v0.body_pos * p0 + v1.body_pos * p1 + v2.body_pos * p2;
+        let world_normal = (v0.world_normal * p0 + v1.world_normal * p1 + v2.world_normal * p2).normalize();
+        let uv = v0.uv * p0 + v1.uv * p1 + v2.uv * p2;
+
+        // Llama al sombreador de fragmentos para obtener el color y la opacidad del píxel.
+        let (color, alpha) = shader.fragment_with_alpha(&self.fragment_context(world_pos, local_pos, body_pos, world_normal, uv, time));
+        Some((color, alpha, depth))
     }
 }
 
 // Estructura auxiliar para almacenar los datos de un vértice después de ser transformado.
+#[derive(Clone, Copy)]
 struct TransformedVertex {
+    clip_pos: Vec4,     // Posición en el espacio de recorte, antes de la división de perspectiva.
     screen_pos: Vec2,   // Posición en el espacio de la pantalla.
     depth: f32,         // Profundidad del vértice (coordenada Z en NDC).
+    inv_w: f32,         // Inverso de la coordenada W de recorte, para la interpolación con perspectiva.
     world_pos: Vec3,    // Posición en el espacio del mundo.
+    local_pos: Vec3,    // Posición en el espacio del objeto.
+    body_pos: Vec3,     // Posición en el marco del cuerpo (ver `Renderer::set_body_frame`).
+    view_depth: f32,    // Distancia al plano de la cámara en el espacio de la vista.
     world_normal: Vec3, // Normal en el espacio del mundo.
+    uv: Vec2,           // Coordenadas de textura del vértice.
+    color: Vec3,        // Color sombreado en el vértice; solo se calcula con sombreado Gouraud.
+    alpha: f32,         // Opacidad sombreada en el vértice; solo se calcula con sombreado Gouraud.
+}
+
+impl TransformedVertex {
+    // Indica si el vértice está entre los planos cercano y lejano.
+    #[inline]
+    fn in_depth_range(&self) -> bool {
+        (-1.0..=1.0).contains(&self.depth)
+    }
+
+    // Distancia con signo al plano cercano en el espacio de recorte (z = -w); es negativa detrás del plano.
+    // Con una proyección en perspectiva los puntos delante del plano también tienen W positiva.
+    #[inline]
+    fn near_distance(&self) -> f32 {
+        self.clip_pos.z + self.clip_pos.w
+    }
+
+    // Interpola linealmente la posición de recorte y los atributos entre dos vértices. Los campos de
+    // pantalla no se interpolan: deben recalcularse con `Renderer::project`.
+    fn lerp(a: &TransformedVertex, b: &TransformedVertex, t: f32) -> TransformedVertex {
+        TransformedVertex {
+            clip_pos: a.clip_pos.lerp(&b.clip_pos, t),
+            screen_pos: Vec2::zeros(),
+            depth: 0.0,
+            inv_w: 0.0,
+            world_pos: a.world_pos.lerp(&b.world_pos, t),
+            local_pos: a.local_pos.lerp(&b.local_pos, t),
+            body_pos: a.body_pos.lerp(&b.body_pos, t),
+            view_depth: a.view_depth + (b.view_depth - a.view_depth) * t,
+            world_normal: a.world_normal.lerp(&b.world_normal, t).normalize(),
+            uv: a.uv.lerp(&b.uv, t),
+            color: a.color.lerp(&b.color, t),
+            alpha: a.alpha + (b.alpha - a.alpha) * t,
+        }
+    }
+}
+
+// Convierte coordenadas baricéntricas del espacio de pantalla en pesos con corrección de perspectiva.
+// Los atributos divididos por W sí varían linealmente en pantalla, así que se pondera cada peso por 1/W
+// y se normaliza. La profundidad en NDC ya es lineal en pantalla y no necesita esta corrección.
+#[inline]
+fn perspective_correct(bary: (f32, f32, f32), inv_w0: f32, inv_w1: f32, inv_w2: f32) -> (f32, f32, f32) {
+    let (w0, w1, w2) = bary;
+    let p0 = w0 * inv_w0;
+    let p1 = w1 * inv_w1;
+    let p2 = w2 * inv_w2;
+    let sum = p0 + p1 + p2;
+
+    if sum.abs() < 1e-8 {
+        return bary; // Vértices degenerados: se usa la interpolación lineal.
+    }
+
+    (p0 / sum, p1 / sum, p2 / sum)
+}
+
+// Calcula la matriz de normales (inversa transpuesta de la parte 3x3) de una matriz de modelo.
+// Si la matriz no es invertible (por ejemplo, con escala cero), usa la parte 3x3 directamente.
+pub fn normal_matrix(model_matrix: &Mat4) -> Mat3 {
+    let upper = mat4_to_mat3(model_matrix);
+    upper.try_inverse().map(|inv| inv.transpose()).unwrap_or(upper)
 }
 
 // Calcula las coordenadas baricéntricas de un punto `p` con respecto a un triángulo (a, b, c).
@@ -169,7 +837,7 @@ fn barycentric(p: &Vec2, a: &Vec2, b: &Vec2, c: &Vec2) -> (f32, f32, f32) {
     let denom = d00 * d11 - d01 * d01;
 
     if denom.abs() < 1e-8 {
-        return (0.0, 0.0, 0.0); // Triángulo degenerado.
+        return (-1.0, -1.0, -1.0); // Triángulo degenerado: ningún punto queda dentro.
     }
 
     let v = (d11 * d20 - d01 * d21) / denom;
@@ -178,3 +846,227 @@ fn barycentric(p: &Vec2, a: &Vec2, b: &Vec2, c: &Vec2) -> (f32, f32, f32) {
 
     (u, v, w)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::SolidShader;
+    use nalgebra_glm::perspective;
+
+    const SIZE: usize = 32;
+
+    // Proyección en perspectiva para un búfer cuadrado de `SIZE`×`SIZE`.
+    fn projection() -> Mat4 {
+        perspective(1.0, 60f32.to_radians(), 0.1, 100.0)
+    }
+
+    // Malla de un solo triángulo con los vértices en el orden dado.
+    fn triangle(positions: [Vec3; 3]) -> ObjMesh {
+        ObjMesh {
+            vertices: positions
+                .iter()
+                .map(|&position| Vertex { position, normal: Vec3::z(), uv: Vec2::zeros(), tangent: Vec3::zeros() })
+                .collect(),
+            indices: vec![0, 1, 2],
+            material: None,
+        }
+    }
+
+    // Dibuja una malla con la cámara en el origen mirando hacia -Z.
+    fn render(renderer: &mut Renderer, framebuffer: &mut Framebuffer, mesh: &ObjMesh) -> RenderStats {
+        let shader = SolidShader::from_material(None);
+        let draw = DrawCall {
+            mesh,
+            shader: &shader,
+            material: Material::default(),
+            model_matrix: Mat4::identity(),
+            time: 0.0,
+        };
+        renderer.render_mesh(framebuffer, &draw, &Mat4::identity(), &projection())
+    }
+
+    // Triángulo antihorario visto desde la cámara, delante de ella.
+    fn front_facing() -> [Vec3; 3] {
+        [Vec3::new(-1.0, -1.0, -3.0), Vec3::new(1.0, -1.0, -3.0), Vec3::new(0.0, 1.0, -3.0)]
+    }
+
+    #[test]
+    fn back_facing_triangle_leaves_buffers_untouched() {
+        let mut renderer = Renderer::new(SIZE, SIZE, 1);
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        let (color, depth) = (framebuffer.buffer.clone(), renderer.depth_buffer.clone());
+
+        let [a, b, c] = front_facing();
+        let stats = render(&mut renderer, &mut framebuffer, &triangle([a, c, b]));
+
+        assert_eq!(stats.culled, 1);
+        assert_eq!(stats.pixels_written, 0);
+        assert_eq!(framebuffer.buffer, color);
+        assert_eq!(renderer.depth_buffer, depth);
+    }
+
+    #[test]
+    fn front_facing_triangle_is_drawn() {
+        let mut renderer = Renderer::new(SIZE, SIZE, 1);
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+
+        let stats = render(&mut renderer, &mut framebuffer, &triangle(front_facing()));
+
+        assert_eq!(stats.submitted, 1);
+        assert_eq!(stats.drawn, 1);
+        assert_eq!(stats.culled, 0);
+        assert!(stats.pixels_written > 0);
+    }
+
+    #[test]
+    fn the_renderer_depth_buffer_keeps_the_nearest_triangle() {
+        let mut renderer = Renderer::new(SIZE, SIZE, 1);
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        let far = triangle(front_facing().map(|v| Vec3::new(v.x * 3.0, v.y * 3.0, -6.0)));
+
+        // El triángulo cercano se dibuja primero y el lejano, que lo cubre en pantalla, no lo tapa.
+        render(&mut renderer, &mut framebuffer, &triangle(front_facing()));
+        let near = renderer.depth_buffer.clone();
+        let stats = render(&mut renderer, &mut framebuffer, &far);
+        let center = (SIZE / 2) * SIZE + SIZE / 2;
+        assert!(near[center] < f32::INFINITY);
+        assert_eq!(renderer.depth_buffer[center], near[center]);
+        assert!(stats.pixels_written > 0); // Alrededor del cercano, donde el lejano sí se ve.
+
+        // Con `Always` el último triángulo gana, y `clear_depth` vacía el búfer.
+        renderer.depth_func = DepthFunc::Always;
+        render(&mut renderer, &mut framebuffer, &far);
+        assert!(renderer.depth_buffer[center] > near[center]);
+        renderer.clear_depth();
+        assert!(renderer.depth_buffer.iter().all(|&depth| depth == f32::INFINITY));
+    }
+
+    #[test]
+    fn near_clip_keeps_vertices_in_front_of_the_camera() {
+        let mut renderer = Renderer::new(SIZE, SIZE, 1);
+        let identity = Mat4::identity();
+        let mvp = projection();
+
+        // Un vértice detrás de la cámara y dos delante: el recorte deja un cuadrilátero.
+        let mesh = triangle([Vec3::new(-1.0, 0.0, -3.0), Vec3::new(1.0, 0.0, -3.0), Vec3::new(0.0, 0.5, 2.0)]);
+        let transformed: Vec<_> = mesh
+            .vertices
+            .iter()
+            .map(|v| renderer.transform_vertex(v, &identity, &identity, &Mat3::identity(), &identity, &mvp))
+            .collect();
+        let polygon = renderer.clip_near([&transformed[0], &transformed[1], &transformed[2]]);
+
+        assert_eq!(polygon.len(), 4);
+        for vertex in &polygon {
+            assert!(vertex.clip_pos.w > 1e-6, "w = {}", vertex.clip_pos.w);
+            assert!(vertex.near_distance() >= -1e-5);
+        }
+
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        let stats = render(&mut renderer, &mut framebuffer, &mesh);
+        assert_eq!(stats.clipped, 1);
+        assert_eq!(stats.drawn, 2);
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular_under_non_uniform_scale() {
+        let model = nalgebra_glm::scale(&Mat4::identity(), &Vec3::new(3.0, 1.0, 0.5));
+
+        // Una superficie inclinada: su tangente y su normal dejan de ser perpendiculares si la normal
+        // se transforma con la misma matriz que las posiciones.
+        let tangent = Vec3::new(1.0, -1.0, 1.0).normalize();
+        let normal = Vec3::new(1.0, 1.0, 0.0).normalize();
+
+        let world_tangent = mat4_to_mat3(&model) * tangent;
+        let world_normal = (normal_matrix(&model) * normal).normalize();
+
+        assert!((world_normal.norm() - 1.0).abs() < 1e-5);
+        assert!(world_normal.dot(&world_tangent).abs() < 1e-5);
+    }
+
+    #[test]
+    fn perspective_correct_uv_matches_the_surface_point() {
+        let renderer = Renderer::new(SIZE, SIZE, 1);
+        let identity = Mat4::identity();
+        let mvp = projection();
+
+        // Triángulo sesgado con vértices a distancias muy distintas de la cámara.
+        let positions = [Vec3::new(-1.0, -1.0, -2.0), Vec3::new(2.0, -1.0, -6.0), Vec3::new(0.0, 1.5, -3.0)];
+        let uvs = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0)];
+        let vertices: Vec<_> = positions
+            .iter()
+            .zip(uvs)
+            .map(|(&position, uv)| {
+                let vertex = Vertex { position, normal: Vec3::z(), uv, tangent: Vec3::zeros() };
+                renderer.transform_vertex(&vertex, &identity, &identity, &Mat3::identity(), &identity, &mvp)
+            })
+            .collect();
+
+        // UV interpoladas en el centroide del triángulo en pantalla.
+        let centroid = (vertices[0].screen_pos + vertices[1].screen_pos + vertices[2].screen_pos) / 3.0;
+        let weights = barycentric(&centroid, &vertices[0].screen_pos, &vertices[1].screen_pos, &vertices[2].screen_pos);
+        let (p0, p1, p2) = perspective_correct(weights, vertices[0].inv_w, vertices[1].inv_w, vertices[2].inv_w);
+        let uv = uvs[0] * p0 + uvs[1] * p1 + uvs[2] * p2;
+
+        // Valor analítico: el rayo de la cámara por ese píxel corta el plano del triángulo en un punto cuyas
+        // coordenadas baricéntricas en el espacio de la vista dan las UV.
+        let tan_half = (60f32.to_radians() * 0.5).tan();
+        let ndc = Vec2::new(centroid.x / SIZE as f32 * 2.0 - 1.0, 1.0 - centroid.y / SIZE as f32 * 2.0);
+        let ray = Vec3::new(ndc.x * tan_half, ndc.y * tan_half, -1.0);
+        let [a, b, c] = positions;
+        let plane_normal = (b - a).cross(&(c - a));
+        let point = ray * (plane_normal.dot(&a) / plane_normal.dot(&ray));
+        let area = |p: Vec3, q: Vec3, r: Vec3| (q - p).cross(&(r - p)).dot(&plane_normal);
+        let total = area(a, b, c);
+        let expected = (uvs[0] * area(point, b, c) + uvs[1] * area(a, point, c) + uvs[2] * area(a, b, point)) / total;
+
+        assert!((uv - expected).norm() < 1e-4, "{uv:?} != {expected:?}");
+
+        // La interpolación lineal en pantalla da otro valor en este triángulo.
+        let linear = (uvs[0] + uvs[1] + uvs[2]) / 3.0;
+        assert!((linear - expected).norm() > 0.05);
+    }
+
+    #[test]
+    fn wireframe_draws_the_part_of_a_triangle_in_front_of_the_far_plane() {
+        let mut renderer = Renderer::new(SIZE, SIZE, 1);
+        renderer.render_mode = RenderMode::Wireframe;
+        renderer.cull_backfaces = false;
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+
+        // El vértice superior queda detrás del plano lejano (100): antes se descartaba el triángulo completo.
+        let mesh = triangle([Vec3::new(-1.0, -1.0, -3.0), Vec3::new(1.0, -1.0, -3.0), Vec3::new(0.0, 1.0, -300.0)]);
+        let stats = render(&mut renderer, &mut framebuffer, &mesh);
+
+        assert!(stats.pixels_written > SIZE / 2, "solo se escribieron {} píxeles", stats.pixels_written);
+        assert!(renderer.depth_buffer.iter().all(|&depth| depth == f32::INFINITY || (-1.0..=1.0).contains(&depth)));
+    }
+
+    #[test]
+    fn binning_into_bands_matches_a_single_band() {
+        let mesh = ObjMesh::create_sphere(1.0, 12, 16);
+        let shader = SolidShader::from_material(None);
+        let view = nalgebra_glm::translation(&Vec3::new(0.0, 0.0, -3.0));
+        let sphere = DrawCall {
+            mesh: &mesh,
+            shader: &shader,
+            material: Material::default(),
+            model_matrix: Mat4::identity(),
+            time: 0.0,
+        };
+        let draw = |tile_rows: usize, parallel: bool| {
+            let mut renderer = Renderer::new(SIZE, SIZE, 1);
+            renderer.tile_rows = tile_rows;
+            renderer.parallel = parallel;
+            let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+            renderer.render_mesh(&mut framebuffer, &sphere, &view, &projection());
+            (framebuffer.buffer, renderer.depth_buffer)
+        };
+
+        // Una sola franja recibe todos los triángulos, como un rasterizador sin binning.
+        let single = draw(SIZE, false);
+        assert_eq!(draw(TILE_ROWS, false), single);
+        assert_eq!(draw(TILE_ROWS, true), single);
+        assert_eq!(draw(5, true), single);
+    }
+}
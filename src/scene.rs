@@ -0,0 +1,468 @@
+// Importa los tipos necesarios para describir escenas en un archivo TOML y convertirlas en objetos renderizables.
+use crate::framebuffer::{BlendMode, BloomSettings, Color}; // Para los ajustes de bloom, el fondo de cada escena y la mezcla de los translúcidos.
+use crate::mesh::ObjMesh; // Para las mallas de los objetos.
+use crate::orbit::Orbit; // Para las órbitas de los objetos alrededor de su padre.
+use crate::shaders::{shader_from_name, Material, PlanetShader, ShaderParams, SolidShader, TextureMapping, TexturedShader}; // Para crear sombreadores a partir de su nombre.
+use crate::texture::{AddressMode, SampleMode, Texture}; // Para las texturas que usa "TexturedShader".
+use crate::RenderObject; // El objeto renderizable que usa el loop principal.
+use nalgebra_glm::Vec3; // Para posiciones y ejes de rotación.
+use serde::{Deserialize, Deserializer}; // Para leer la descripción de la escena desde TOML.
+use std::path::Path;
+
+// Describe un archivo de escenas completo: una lista de escenas seleccionables con las teclas numéricas.
+#[derive(Debug, Deserialize)]
+pub struct SceneFile {
+    #[serde(default)]
+    pub textures: Vec<TextureDef>, // Imágenes de textura; los objetos las eligen por su posición en la lista.
+    pub scenes: Vec<SceneDef>, // Escenas en el orden en que se seleccionan.
+}
+
+// Describe una textura: solo la ruta de la imagen, o una tabla con la ruta y cómo se muestrea. Los campos omitidos
+// de la tabla usan los de `Texture::from_colors`: filtro bilineal, repetición en U y borde en V.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum TextureDef {
+    Path(String),
+    Table {
+        path: String,
+        filter: Option<String>, // "nearest" o "bilinear".
+        wrap_u: Option<String>, // "wrap" o "clamp".
+        wrap_v: Option<String>, // "wrap" o "clamp".
+    },
+}
+
+impl TextureDef {
+    // Carga la imagen y le aplica el filtro y el direccionamiento de la tabla.
+    fn load(&self) -> Result<Texture, String> {
+        let (path, filter, wrap_u, wrap_v) = match self {
+            TextureDef::Path(path) => return Texture::load(path),
+            TextureDef::Table { path, filter, wrap_u, wrap_v } => (path, filter, wrap_u, wrap_v),
+        };
+
+        let mut texture = Texture::load(path)?;
+        if let Some(name) = filter {
+            texture.sample_mode = match name.as_str() {
+                "nearest" => SampleMode::Nearest,
+                "bilinear" => SampleMode::Bilinear,
+                other => {
+                    return Err(format!("texture '{}': unknown filter '{}': expected nearest or bilinear", path, other))
+                }
+            };
+        }
+        for (axis, wrap) in [wrap_u, wrap_v].into_iter().enumerate() {
+            if let Some(name) = wrap {
+                texture.address[axis] = match name.as_str() {
+                    "wrap" => AddressMode::Wrap,
+                    "clamp" => AddressMode::Clamp,
+                    other => {
+                        return Err(format!("texture '{}': unknown wrap mode '{}': expected wrap or clamp", path, other))
+                    }
+                };
+            }
+        }
+        Ok(texture)
+    }
+}
+
+// Describe una escena: un nombre para mostrar en pantalla y los objetos que contiene.
+#[derive(Debug, Deserialize)]
+pub struct SceneDef {
+    pub name: String, // Nombre mostrado en la interfaz.
+    #[serde(default)]
+    pub objects: Vec<ObjectDef>, // Objetos de la escena.
+    #[serde(default)]
+    pub bloom: BloomDef, // Ajustes del bloom para esta escena.
+    #[serde(default)]
+    pub background: BackgroundDef, // Degradado del fondo de esta escena.
+}
+
+// Describe el degradado vertical del fondo de una escena, con colores escritos como "#RRGGBB".
+// Los campos omitidos usan el fondo de las escenas integradas.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BackgroundDef {
+    #[serde(deserialize_with = "hex_color")]
+    pub top: Color, // Color de la primera fila.
+    #[serde(deserialize_with = "hex_color")]
+    pub bottom: Color, // Color de la última fila.
+}
+
+impl Default for BackgroundDef {
+    fn default() -> Self {
+        let [top, bottom] = DEFAULT_BACKGROUND;
+        BackgroundDef { top, bottom }
+    }
+}
+
+// Fondo de las escenas integradas: un degradado muy oscuro hacia azul que da algo de profundidad sin competir
+// con los planetas.
+pub const DEFAULT_BACKGROUND: [Color; 2] = [Color::BLACK, Color { r: 8, g: 10, b: 28 }];
+
+// Lee un color en hexadecimal con `Color::from_hex`; el error de formato se informa con la línea del archivo.
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    Color::from_hex(&text).map_err(serde::de::Error::custom)
+}
+
+// Describe los ajustes de bloom de una escena. Los campos omitidos usan los valores de `BloomSettings::default`.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BloomDef {
+    pub threshold: f32,
+    pub blur_radius: usize,
+    pub intensity: f32,
+}
+
+impl Default for BloomDef {
+    fn default() -> Self {
+        let settings = BloomSettings::default();
+        BloomDef {
+            threshold: settings.threshold,
+            blur_radius: settings.blur_radius,
+            intensity: settings.intensity,
+        }
+    }
+}
+
+// Describe un objeto de la escena. Los campos opcionales usan los mismos valores por defecto que `RenderObject::new`.
+#[derive(Debug, Deserialize)]
+pub struct ObjectDef {
+    pub shader: String, // Nombre del sombreador, por ejemplo "GasGiant".
+    pub mesh: String,   // "sphere", "icosphere", "ring", "cube", "torus", "cylinder", "plane", "obj:<ruta>" o "heightmap:<ruta>".
+    #[serde(default)]
+    pub subdivisions: u32, // Veces que se divide cada triángulo de la malla en cuatro, para suavizar mallas de pocos polígonos.
+    #[serde(default)]
+    pub position: [f32; 3], // Posición en el mundo.
+    #[serde(default = "default_one")]
+    pub scale: f32, // Escala uniforme.
+    #[serde(default = "default_one")]
+    pub rotation_speed: f32, // Velocidad de rotación en radianes por segundo.
+    #[serde(default = "default_axis")]
+    pub rotation_axis: [f32; 3], // Eje de rotación (se normaliza al cargar).
+    #[serde(default)]
+    pub axial_tilt: f32, // Inclinación fija del eje en grados, hacia +Z.
+    #[serde(default)]
+    pub double_sided: bool, // Si es verdadero, no se aplica backface culling.
+    #[serde(default)]
+    pub back_faces_only: bool, // Si es verdadero, se dibujan solo las caras traseras (capas de atmósfera).
+    #[serde(default = "default_true")]
+    pub opaque: bool, // Si es falso, se dibuja después de los opacos y sin escribir profundidad (nubes, anillos).
+    #[serde(default = "default_blend", deserialize_with = "blend_mode")]
+    pub blend: BlendMode, // "alpha", "add" o "multiply": cómo se combina con lo de atrás si no es opaco.
+    #[serde(default)]
+    pub seed: u32, // Semilla del ruido del sombreador; dos objetos con semillas distintas se ven diferentes.
+    #[serde(default = "default_one")]
+    pub noise_scale: f32, // Frecuencia del ruido del sombreador.
+    #[serde(default)]
+    pub texture: usize, // Índice en la lista `textures` del archivo, usado por "TexturedShader".
+    #[serde(default)]
+    pub equirectangular: bool, // Si es verdadero, la textura se mapea por longitud y latitud en vez de por UV.
+    pub material: Option<MaterialDef>, // Coeficientes de iluminación; los omitidos usan los del sombreador.
+    pub orbit: Option<OrbitDef>, // Órbita alrededor del padre; si se define, reemplaza a `position`.
+    #[serde(default)]
+    pub children: Vec<ObjectDef>, // Objetos cuya posición es relativa a este y que se mueven con él.
+}
+
+// Describe los coeficientes de iluminación de un objeto. Cada campo omitido conserva el valor
+// del material por defecto del sombreador.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MaterialDef {
+    pub ambient: Option<f32>,
+    pub diffuse: Option<f32>,
+    pub specular: Option<f32>,
+    pub shininess: Option<f32>,
+}
+
+impl MaterialDef {
+    // Aplica los campos definidos sobre `base`.
+    fn apply(&self, base: Material) -> Material {
+        Material {
+            ambient: self.ambient.unwrap_or(base.ambient),
+            diffuse: self.diffuse.unwrap_or(base.diffuse),
+            specular: self.specular.unwrap_or(base.specular),
+            shininess: self.shininess.unwrap_or(base.shininess),
+        }
+    }
+}
+
+// Describe una órbita. Una órbita circular solo necesita `radius`; una elíptica usa `semi_major` y `semi_minor`.
+// Los ángulos se escriben en grados, que son más fáciles de leer en el archivo.
+#[derive(Debug, Deserialize)]
+pub struct OrbitDef {
+    pub radius: Option<f32>,
+    pub semi_major: Option<f32>, // Si se omite, se usa `radius`.
+    pub semi_minor: Option<f32>, // Si se omite, es igual al semieje mayor.
+    #[serde(default = "default_one")]
+    pub speed: f32, // Radianes por segundo, como `rotation_speed`.
+    #[serde(default)]
+    pub inclination: f32,
+    #[serde(default)]
+    pub phase: f32,
+}
+
+impl OrbitDef {
+    // Convierte la descripción en una órbita, pasando los ángulos a radianes.
+    fn to_orbit(&self) -> Result<Orbit, String> {
+        let semi_major = self
+            .semi_major
+            .or(self.radius)
+            .ok_or_else(|| "orbit needs `radius` or `semi_major`".to_string())?;
+        let semi_minor = self.semi_minor.unwrap_or(semi_major);
+        if semi_minor > semi_major {
+            return Err(format!("orbit semi_minor ({}) is larger than semi_major ({})", semi_minor, semi_major));
+        }
+
+        Ok(Orbit {
+            semi_major,
+            semi_minor,
+            speed: self.speed,
+            inclination: self.inclination.to_radians(),
+            phase: self.phase.to_radians(),
+        })
+    }
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_axis() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_blend() -> BlendMode {
+    BlendMode::Alpha
+}
+
+// Lee el modo de mezcla de un objeto por su nombre en minúsculas.
+fn blend_mode<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BlendMode, D::Error> {
+    match String::deserialize(deserializer)?.as_str() {
+        "replace" => Ok(BlendMode::Replace),
+        "alpha" => Ok(BlendMode::Alpha),
+        "add" => Ok(BlendMode::Add),
+        "multiply" => Ok(BlendMode::Multiply),
+        other => Err(serde::de::Error::custom(format!(
+            "unknown blend mode '{}': expected replace, alpha, add or multiply",
+            other
+        ))),
+    }
+}
+
+// Lee un archivo de escenas. Devuelve `Ok(None)` si el archivo no existe, para usar las escenas integradas.
+pub fn load_scene_file(path: &str) -> Result<Option<SceneFile>, String> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path, e))?;
+    let file: SceneFile = toml::from_str(&text).map_err(|e| format!("Error parsing {}: {}", path, e))?;
+
+    if file.scenes.is_empty() {
+        return Err(format!("{} does not define any scene", path));
+    }
+
+    Ok(Some(file))
+}
+
+impl SceneFile {
+    // Devuelve los nombres de las escenas en orden.
+    pub fn names(&self) -> Vec<String> {
+        self.scenes.iter().map(|scene| scene.name.clone()).collect()
+    }
+
+    // Devuelve los ajustes de bloom de las escenas en orden.
+    pub fn bloom_settings(&self) -> Vec<BloomSettings> {
+        self.scenes
+            .iter()
+            .map(|scene| BloomSettings {
+                threshold: scene.bloom.threshold,
+                blur_radius: scene.bloom.blur_radius,
+                intensity: scene.bloom.intensity,
+            })
+            .collect()
+    }
+
+    // Devuelve los colores del fondo de las escenas en orden, como [arriba, abajo].
+    pub fn backgrounds(&self) -> Vec<[Color; 2]> {
+        self.scenes.iter().map(|scene| [scene.background.top, scene.background.bottom]).collect()
+    }
+
+    // Carga las texturas listadas en el archivo, en el mismo orden, junto con los errores de las que fallaron.
+    // Cada una que falla se reemplaza por una gris, para que los índices de las demás no cambien y solo los
+    // objetos que la usan se vean grises.
+    pub fn load_textures(&self) -> (Vec<Texture>, Vec<String>) {
+        let mut errors = Vec::new();
+        let textures = self
+            .textures
+            .iter()
+            .map(|def| {
+                def.load().unwrap_or_else(|e| {
+                    errors.push(e);
+                    Texture::solid(Color::new(128, 128, 128))
+                })
+            })
+            .collect();
+        (textures, errors)
+    }
+
+    // Construye los objetos renderizables de todas las escenas.
+    // `sphere` y `ring` son las mallas usadas para los valores "sphere" y "ring" del campo `mesh`.
+    pub fn build(&self, sphere: &ObjMesh, ring: &ObjMesh) -> Result<Vec<Vec<RenderObject>>, String> {
+        self.scenes
+            .iter()
+            .map(|scene| {
+                scene
+                    .objects
+                    .iter()
+                    .map(|object| object.build(sphere, ring).map_err(|e| format!("Scene '{}': {}", scene.name, e)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl ObjectDef {
+    // Convierte la descripción en un `RenderObject`, cargando la malla y creando el sombreador, junto con sus hijos.
+    fn build(&self, sphere: &ObjMesh, ring: &ObjMesh) -> Result<RenderObject, String> {
+        let mut mesh = match self.mesh.as_str() {
+            "sphere" => sphere.clone(),
+            "ring" => ring.clone(),
+            "icosphere" => ObjMesh::create_icosphere(1.0, 4),
+            "cube" => ObjMesh::create_cube(2.0), // Misma extensión que la esfera de radio 1.
+            "torus" => ObjMesh::create_torus(1.65, 0.35, 100, 24), // Ocupa la misma banda que el anillo plano.
+            "cylinder" => ObjMesh::create_cylinder(1.0, 2.0, 48, true),
+            "plane" => ObjMesh::create_plane(2.0, 2.0, 32, 32), // Cuadrado de lado 2, como la sección de la esfera.
+            other => match other.strip_prefix("obj:") {
+                Some(path) => {
+                    // Se unen todos los modelos del archivo para que los .obj de varias partes se vean completos,
+                    // y se sueldan los vértices repetidos entre ellos.
+                    // Los .obj no siempre están centrados ni a la misma escala; se centran para que roten
+                    // en su lugar y se ajustan a radio 1 para que `scale` signifique lo mismo que en la esfera.
+                    let mut mesh = ObjMesh::load_merged_from_obj(path)?;
+                    mesh.weld_vertices(1e-5);
+                    mesh.center();
+                    mesh.normalize_scale(1.0);
+                    mesh
+                }
+                // Terreno de lado 2 como el plano, con relieve de hasta 0.5 según la luminancia de la imagen.
+                None => match other.strip_prefix("heightmap:") {
+                    Some(path) => ObjMesh::from_heightmap(path, Vec3::new(2.0, 0.5, 2.0))?,
+                    None => return Err(format!("unknown mesh '{}'", other)),
+                },
+            },
+        };
+
+        for _ in 0..self.subdivisions {
+            mesh.subdivide();
+        }
+
+        // "Solid" usa los colores del material de la malla, así que se crea después de cargarla.
+        let shader: Box<dyn PlanetShader> = if self.shader == "Solid" {
+            Box::new(SolidShader::from_material(mesh.material.as_ref()))
+        } else if self.shader == "TexturedShader" {
+            let mapping = if self.equirectangular {
+                TextureMapping::Equirectangular
+            } else {
+                TextureMapping::MeshUv
+            };
+            Box::new(TexturedShader {
+                texture: self.texture,
+                mapping,
+            })
+        } else {
+            let params = ShaderParams {
+                seed: self.seed,
+                noise_scale: self.noise_scale,
+            };
+            shader_from_name(&self.shader, params).ok_or_else(|| format!("unknown shader '{}'", self.shader))?
+        };
+
+        let material = self.material.as_ref().map(|def| def.apply(shader.default_material()));
+        let [x, y, z] = self.position;
+        let [ax, ay, az] = self.rotation_axis;
+        // Un eje nulo no se puede normalizar y llenaría de NaN la matriz del modelo.
+        let rotation_axis = Vec3::new(ax, ay, az);
+        if rotation_axis.norm() < 1e-6 {
+            return Err("rotation_axis must not be zero".to_string());
+        }
+        let orbit = self.orbit.as_ref().map(OrbitDef::to_orbit).transpose()?;
+        let children = self
+            .children
+            .iter()
+            .map(|child| child.build(sphere, ring))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RenderObject {
+            mesh,
+            shader,
+            position: Vec3::new(x, y, z),
+            scale: self.scale,
+            rotation_speed: self.rotation_speed,
+            rotation_axis: rotation_axis.normalize(),
+            axial_tilt: self.axial_tilt.to_radians(),
+            double_sided: self.double_sided,
+            back_faces_only: self.back_faces_only,
+            opaque: self.opaque,
+            blend: self.blend,
+            material,
+            orbit,
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_rotation_axis_is_rejected() {
+        let file: SceneFile = toml::from_str(
+            r#"
+            [[scenes]]
+            name = "Eje"
+
+            [[scenes.objects]]
+            mesh = "cube"
+            shader = "Solid"
+            rotation_axis = [0.0, 0.0, 0.0]
+            "#,
+        )
+        .unwrap();
+        let sphere = ObjMesh::create_icosphere(1.0, 0);
+        let ring = ObjMesh::create_plane(2.0, 2.0, 1, 1);
+
+        let err = file.build(&sphere, &ring).err().expect("a zero axis must fail");
+        assert_eq!(err, "Scene 'Eje': rotation_axis must not be zero");
+    }
+
+    #[test]
+    fn a_missing_texture_is_replaced_without_shifting_the_others() {
+        let path = std::env::temp_dir().join(format!("lab4_textures_{}.png", std::process::id()));
+        image::RgbImage::from_pixel(2, 1, image::Rgb([255, 0, 0])).save(&path).expect("image should be written");
+        let text = format!(
+            r#"
+            textures = ["missing.png", {{ path = "{}", filter = "nearest" }}]
+
+            [[scenes]]
+            name = "Texturas"
+            "#,
+            path.display()
+        );
+        let file: SceneFile = toml::from_str(&text).unwrap();
+
+        let (textures, errors) = file.load_textures();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(textures.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("missing.png"), "{}", errors[0]);
+        // La que falta queda gris y la siguiente conserva su índice.
+        assert_eq!(textures[0].data, [Color::new(128, 128, 128)]);
+        assert_eq!((textures[1].width, textures[1].data[0]), (2, Color::new(255, 0, 0)));
+    }
+}
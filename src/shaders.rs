@@ -1,21 +1,307 @@
 // Importa el tipo Color del módulo de framebuffer y Vec3 de nalgebra_glm.
 use crate::framebuffer::Color;
-use nalgebra_glm::Vec3;
+use crate::mesh;
+use crate::texture::Texture;
+use nalgebra_glm::{Mat3, Vec2, Vec3};
 use std::f32::consts::PI;
 
+// Número máximo de luces direccionales que se entregan a los sombreadores.
+pub const MAX_LIGHTS: usize = 4;
+
+// Define una luz direccional, como la de una estrella lejana.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    pub dir: Vec3,      // Dirección normalizada hacia la luz.
+    pub color: Vec3,    // Color de la luz, con componentes entre 0.0 y 1.0.
+    pub intensity: f32, // Intensidad que multiplica al color.
+}
+
+impl DirectionalLight {
+    // Crea una luz blanca de intensidad 1 en la dirección dada.
+    pub fn white(dir: Vec3) -> Self {
+        DirectionalLight {
+            dir: dir.normalize(),
+            color: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        }
+    }
+}
+
+// Esfera que puede tapar la luz a los demás objetos, como una luna que eclipsa a su planeta.
+#[derive(Debug, Clone, Copy)]
+pub struct SphereOccluder {
+    pub center: Vec3, // Centro en el espacio del mundo.
+    pub radius: f32,  // Radio en unidades del mundo.
+}
+
+// Anillo plano que filtra la luz según su densidad, como los de Saturno sobre su planeta. Los radios corresponden
+// a los de la malla (`RING_RADII`) multiplicados por la escala del objeto.
+#[derive(Debug, Clone, Copy)]
+pub struct RingOccluder {
+    pub center: Vec3,      // Centro en el espacio del mundo.
+    pub normal: Vec3,      // Normal normalizada del plano del anillo.
+    pub inner_radius: f32, // Radio interior en unidades del mundo.
+    pub outer_radius: f32, // Radio exterior en unidades del mundo.
+}
+
+// Agrupa los datos que el renderizador entrega a un sombreador para cada fragmento.
+pub struct FragmentContext<'a> {
+    pub world_position: Vec3,           // Posición del fragmento en el espacio del mundo.
+    pub local_position: Vec3,           // Posición del fragmento en el espacio del objeto; gira con él.
+    pub body_position: Vec3,            // Posición en el marco del cuerpo: centrada, sin escala y con el eje
+                                        // inclinado, pero sin el giro. Los patrones procedurales se calculan aquí.
+    pub body_to_world: Mat3,            // Rotación que lleva direcciones del marco del cuerpo al mundo.
+    pub normal: Vec3,                   // Normal interpolada en el espacio del mundo.
+    pub uv: Vec2,                       // Coordenadas de textura interpoladas con corrección de perspectiva.
+    pub view_dir: Vec3,                 // Dirección normalizada desde el fragmento hacia la cámara.
+    pub time: f32,                      // Tiempo de la animación en segundos.
+    pub lights: &'a [DirectionalLight], // Luces de la escena. Si está vacía, solo queda la luz ambiental.
+    pub occluders: &'a [SphereOccluder], // Esferas que proyectan sombra sobre los demás objetos.
+    pub rings: &'a [RingOccluder],      // Anillos que proyectan sombra sobre los demás objetos.
+    pub shadow: Option<f32>,            // Fracción de `lights[0]` que deja pasar el mapa de sombras, si hay uno.
+    pub textures: &'a [Texture],        // Texturas cargadas; los sombreadores las eligen por índice.
+    pub material: Material,             // Coeficientes de iluminación del objeto.
+}
+
+// Ancho de la penumbra de un eclipse, en fracciones del radio del cuerpo que tapa la luz.
+const PENUMBRA_WIDTH: f32 = 0.35;
+
+impl FragmentContext<'_> {
+    // Devuelve qué fracción de la luz `lights[light]` alcanza al fragmento: 1.0 a pleno sol y 0.0
+    // dentro de la sombra de alguno de los `occluders`. Es una prueba analítica, sin pasada de profundidad: el
+    // rayo hacia la luz queda tapado si pasa a menos de un radio del centro de la esfera. La penumbra se
+    // suaviza con `smoothstep` sobre la distancia a la que el rayo pasa del borde. Las esferas que contienen al
+    // fragmento se ignoran, para que un cuerpo no se eclipse a sí mismo. Los `rings` atenúan además la luz
+    // según su densidad donde el rayo cruza su plano. Si el renderizador calculó un mapa de sombras, para la
+    // luz 0 reemplaza a la prueba de las esferas: ya contiene a todos los objetos opacos, pero no a los
+    // anillos, que son translúcidos.
+    pub fn light_visibility(&self, light: usize) -> f32 {
+        let light_dir = &self.lights[light].dir;
+        let ring_visibility = self.rings.iter().fold(1.0, |visibility, ring| {
+            // Punto donde el rayo hacia la luz corta el plano del anillo; si es paralelo o el plano queda
+            // detrás del fragmento, no hay sombra. Los fragmentos sobre el plano son los del propio anillo.
+            let facing = ring.normal.dot(light_dir);
+            let height = (ring.center - self.world_position).dot(&ring.normal);
+            if facing.abs() < 1e-4 || height.abs() < 1e-3 {
+                return visibility;
+            }
+            let along = height / facing;
+            if along <= 0.0 {
+                return visibility;
+            }
+            let radius = (self.world_position + light_dir * along - ring.center).magnitude();
+            if radius < ring.inner_radius || radius > ring.outer_radius {
+                return visibility;
+            }
+
+            // Lleva el radio al de la malla para usar la misma densidad con que se dibuja el anillo.
+            let [mesh_inner, mesh_outer] = RING_RADII;
+            let t = (radius - ring.inner_radius) / (ring.outer_radius - ring.inner_radius);
+            visibility * (1.0 - ring_density(mesh_inner + t * (mesh_outer - mesh_inner)))
+        });
+
+        if let (Some(shadow), 0) = (self.shadow, light) {
+            return ring_visibility * shadow;
+        }
+
+        self.occluders.iter().fold(ring_visibility, |visibility, occluder| {
+            let to_center = occluder.center - self.world_position;
+            let along = to_center.dot(light_dir);
+            if along <= 0.0 || to_center.magnitude() <= occluder.radius * 1.01 {
+                return visibility;
+            }
+            let miss = (to_center - light_dir * along).magnitude();
+            let width = occluder.radius * PENUMBRA_WIDTH;
+            visibility * smoothstep(occluder.radius - width, occluder.radius + width, miss)
+        })
+    }
+
+    // Factor difuso de una luz como `Material::diffuse_term`, pero con la parte iluminada atenuada por las
+    // sombras de `light_visibility`. El ambiente no cambia, así que la sombra nunca es más oscura que la noche.
+    pub fn shadowed_diffuse(&self, normal: &Vec3, light: usize) -> f32 {
+        let ambient = self.material.ambient;
+        let light_dir = &self.lights[light].dir;
+        ambient + (self.material.diffuse_term(normal, light_dir) - ambient) * self.light_visibility(light)
+    }
+}
+
+// Coeficientes de iluminación de un objeto. Los sombreadores deciden su color base y dónde aplica cada término;
+// el material solo controla cuánto aporta cada uno. No confundir con `mesh::Material`, que son los colores de un .mtl.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    pub ambient: f32,   // Fracción de luz que recibe el lado nocturno (0.0-1.0).
+    pub diffuse: f32,   // Multiplica la luz difusa por encima del ambiente.
+    pub specular: f32,  // Intensidad del brillo especular; 0.0 lo desactiva.
+    pub shininess: f32, // Exponente de Blinn-Phong; más alto da un brillo más pequeño y concentrado.
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            ambient: 0.15,
+            diffuse: 1.0,
+            specular: 0.0,
+            shininess: 32.0,
+        }
+    }
+}
+
+impl Material {
+    // Factor difuso de una luz: va de `ambient` en el lado nocturno a `ambient + (1 - ambient) * diffuse`
+    // de frente a la luz, con el mismo terminador suave que `lambert`.
+    #[inline]
+    pub fn diffuse_term(&self, normal: &Vec3, light_dir: &Vec3) -> f32 {
+        self.ambient + (1.0 - self.ambient) * self.diffuse * lambert(normal, light_dir, 0.0)
+    }
+
+    // Brillo especular de Blinn-Phong de una luz vista desde `view_dir`, ya multiplicado por `specular`.
+    #[inline]
+    pub fn specular_term(&self, normal: &Vec3, light_dir: &Vec3, view_dir: &Vec3) -> f32 {
+        if self.specular == 0.0 {
+            return 0.0;
+        }
+        let half_vec = (light_dir + view_dir).normalize();
+        normal.dot(&half_vec).max(0.0).powf(self.shininess) * self.specular
+    }
+}
+
+// Parámetros comunes de los sombreadores procedurales. Cambiar la semilla desplaza el dominio del ruido,
+// así que el mismo sombreador produce otro planeta; `noise_scale` agranda o achica los detalles.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderParams {
+    pub seed: u32,        // Semilla del ruido; 0 reproduce el aspecto original.
+    pub noise_scale: f32, // Multiplica la frecuencia del ruido.
+}
+
+impl Default for ShaderParams {
+    fn default() -> Self {
+        ShaderParams { seed: 0, noise_scale: 1.0 }
+    }
+}
+
+impl ShaderParams {
+    // Transforma un punto al espacio de ruido de este sombreador: lo escala y lo desplaza según la semilla.
+    #[inline]
+    fn sample(&self, p: Vec3) -> Vec3 {
+        if self.seed == 0 {
+            return p * self.noise_scale;
+        }
+        let seed = self.seed as i32;
+        let offset = Vec3::new(lattice_hash(seed, 0, 0), lattice_hash(0, seed, 0), lattice_hash(0, 0, seed)) * 1000.0;
+        p * self.noise_scale + offset
+    }
+}
+
 // Define un trait (una interfaz) para los sombreadores de planetas.
 // Cualquier sombreador que implemente este trait debe tener una función `fragment`.
-pub trait PlanetShader {
-    // Calcula el color de un fragmento (píxel) en una posición y normal dadas.
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color;
+// Debe poder compartirse entre hilos, porque el renderizador sombrea varias franjas del búfer a la vez.
+pub trait PlanetShader: Send + Sync {
+    // Calcula el color de un fragmento (píxel) a partir de su contexto.
+    fn fragment(&self, ctx: &FragmentContext) -> Color;
+
+    // Calcula el color y la opacidad (0.0-1.0) de un fragmento. Por defecto los sombreadores son opacos;
+    // los translúcidos lo sobrescriben y el renderizador mezcla su color con lo que ya hay en el búfer.
+    fn fragment_with_alpha(&self, ctx: &FragmentContext) -> (Color, f32) {
+        (self.fragment(ctx), 1.0)
+    }
+
+    // Material con que se ve como fue diseñado. Los objetos que no definen uno propio usan este.
+    fn default_material(&self) -> Material {
+        Material::default()
+    }
+
+    // Indica si el objeto es un anillo plano que proyecta sombra sobre su planeta, con la densidad de
+    // `ring_density`. Solo `RingShader` lo es.
+    fn is_ring(&self) -> bool {
+        false
+    }
+}
+
+// Crea un sombreador a partir de su nombre, tal como aparece en los archivos de escena.
+// `params` se aplica a los sombreadores que lo admiten; los demás lo ignoran.
+pub fn shader_from_name(name: &str, params: ShaderParams) -> Option<Box<dyn PlanetShader>> {
+    let shader: Box<dyn PlanetShader> = match name {
+        "RockyPlanet" => Box::new(RockyPlanet { params, ..Default::default() }),
+        "GasGiant" => Box::new(GasGiant { params, ..Default::default() }),
+        "CrystalPlanet" => Box::new(CrystalPlanet { params }),
+        "LavaPlanet" => Box::new(LavaPlanet { params, ..Default::default() }),
+        "IcePlanet" => Box::new(IcePlanet { params, ..Default::default() }),
+        "RingShader" => Box::new(RingShader),
+        "MoonShader" => Box::new(MoonShader { params, ..Default::default() }),
+        "EarthPlanet" => Box::new(EarthPlanet { params }),
+        "StarShader" => Box::new(StarShader),
+        "DesertPlanet" => Box::new(DesertPlanet { params }),
+        "OceanPlanet" => Box::new(OceanPlanet),
+        "CloudShader" => Box::new(CloudShader { params }),
+        "AtmosphereShader" => Box::new(AtmosphereShader),
+        "Solid" => Box::new(SolidShader::from_material(None)),
+        _ => return None,
+    };
+    Some(shader)
 }
 
 // --- FUNCIONES DE UTILIDAD ---
 
-// Genera un valor de ruido pseudoaleatorio basado en coordenadas 3D.
+// Acumula sobre todas las luces un término escalar calculado a partir de la dirección de cada una,
+// ponderado por su color e intensidad. `term` recibe también el índice de la luz, para las sombras de
+// `FragmentContext::light_visibility`. Con la lista vacía devuelve cero.
+#[inline]
+fn accumulate_lights(lights: &[DirectionalLight], term: impl Fn(usize, &Vec3) -> f32) -> Vec3 {
+    lights
+        .iter()
+        .enumerate()
+        .fold(Vec3::zeros(), |acc, (i, light)| acc + light.color * (light.intensity * term(i, &light.dir)))
+}
+
+// Ancho de la franja de transición entre el día y la noche, en unidades de `normal · luz`.
+const TERMINATOR_WIDTH: f32 = 0.15;
+
+// Iluminación difusa de una luz con un terminador suave. El lado iluminado recibe Lambert normal, la luz se
+// desvanece con `smoothstep` a lo largo de una franja estrecha alrededor del terminador y el lado nocturno
+// queda solo con `ambient`. Devuelve un factor entre `ambient` y 1.0.
+#[inline]
+fn lambert(normal: &Vec3, light_dir: &Vec3, ambient: f32) -> f32 {
+    let n_dot_l = normal.dot(light_dir);
+    let wrapped = ((n_dot_l + TERMINATOR_WIDTH) / (1.0 + TERMINATOR_WIDTH)).max(0.0);
+    let band = smoothstep(-TERMINATOR_WIDTH, TERMINATOR_WIDTH, n_dot_l);
+    ambient + (1.0 - ambient) * wrapped * band
+}
+
+// Genera un valor pseudoaleatorio entre 0.0 y 1.0 para un punto entero de la rejilla.
+#[inline]
+fn lattice_hash(x: i32, y: i32, z: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343) ^ (y as u32).wrapping_mul(0xD816_3841) ^ (z as u32).wrapping_mul(0xCB1A_B31F);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32
+}
+
+// Curva de suavizado quíntica (6t⁵ - 15t⁴ + 10t³), con primera y segunda derivada nulas en 0 y 1.
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+// Genera ruido de valor 3D entre 0.0 y 1.0: valores aleatorios en las esquinas de la rejilla entera,
+// interpolados trilinealmente con `fade`. A diferencia de un hash directo, es continuo.
 #[inline]
 fn noise(x: f32, y: f32, z: f32) -> f32 {
-    ((x * 12.9898 + y * 78.233 + z * 45.164).sin() * 43758.5453).fract()
+    let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+    let (u, v, w) = (fade(x - x0), fade(y - y0), fade(z - z0));
+    let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let corner = |dx: i32, dy: i32, dz: i32| lattice_hash(ix + dx, iy + dy, iz + dz);
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), u);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), u);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), u);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), u);
+
+    lerp(lerp(x00, x10, v), lerp(x01, x11, v), w)
 }
 
 // Interpola suavemente entre dos valores.
@@ -37,42 +323,203 @@ fn mix_vec3(a: Vec3, b: Vec3, t: f32) -> Vec3 {
     a * (1.0 - t) + b * t
 }
 
-// Genera turbulencia sumando múltiples capas de ruido (octavas).
+// Vectores de gradiente para `perlin`: los puntos medios de las aristas de un cubo.
+const PERLIN_GRADIENTS: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0], [0.0, -1.0, 1.0], [0.0, 1.0, -1.0], [0.0, -1.0, -1.0],
+];
+
+// Genera ruido de gradiente 3D (estilo Perlin), aproximadamente entre -1.0 y 1.0.
+// Cada esquina de la rejilla tiene un gradiente aleatorio; el valor es 0 en los puntos enteros
+// y se interpola con la curva quíntica `fade`, lo que da formas más orgánicas que el ruido de valor.
 #[inline]
-fn turbulence(p: Vec3, octaves: i32) -> f32 {
+fn perlin(p: Vec3) -> f32 {
+    let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let (fx, fy, fz) = (p.x - x0, p.y - y0, p.z - z0);
+    let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+    let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let corner = |dx: i32, dy: i32, dz: i32| {
+        let index = (lattice_hash(ix + dx, iy + dy, iz + dz) * 12.0) as usize % 12;
+        let [gx, gy, gz] = PERLIN_GRADIENTS[index];
+        gx * (fx - dx as f32) + gy * (fy - dy as f32) + gz * (fz - dz as f32)
+    };
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), u);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), u);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), u);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), u);
+
+    lerp(lerp(x00, x10, v), lerp(x01, x11, v), w)
+}
+
+// Suma `octaves` capas de ruido (movimiento browniano fraccional). Cada octava multiplica la
+// frecuencia por `lacunarity` y la amplitud por `gain`, lo que controla cuánto detalle fino aparece.
+// El ruido de valor ya está entre 0.0 y 1.0, así que la suma es siempre positiva.
+#[inline]
+fn fbm(p: Vec3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
+    let mut sum = 0.0;
+    let mut freq = 1.0;
+    let mut amp = 1.0;
+    for _ in 0..octaves {
+        sum += amp * noise(p.x * freq, p.y * freq, p.z * freq);
+        freq *= lacunarity;
+        amp *= gain;
+    }
+    sum
+}
+
+// Distancias al punto característico más cercano (F1) y al segundo más cercano (F2) de una rejilla 3D con un
+// punto en cada celda entera, desplazado al azar dentro de ella. Basta revisar la celda de `p` y sus 26 vecinas,
+// porque ningún punto fuera de ellas puede estar más cerca que el de la propia celda.
+fn worley_distances(p: Vec3) -> (f32, f32) {
+    let (ix, iy, iz) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+    let (mut f1, mut f2) = (f32::MAX, f32::MAX);
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (cx, cy, cz) = (ix + dx, iy + dy, iz + dz);
+                // Cada coordenada del desplazamiento usa la celda permutada, para que no salgan iguales.
+                let feature = Vec3::new(
+                    cx as f32 + lattice_hash(cx, cy, cz),
+                    cy as f32 + lattice_hash(cy, cz, cx),
+                    cz as f32 + lattice_hash(cz, cx, cy),
+                );
+                let distance = (feature - p).magnitude();
+                if distance < f1 {
+                    f2 = f1;
+                    f1 = distance;
+                } else if distance < f2 {
+                    f2 = distance;
+                }
+            }
+        }
+    }
+    (f1, f2)
+}
+
+// Genera ruido celular (Worley): la distancia al punto característico más cercano. Vale 0 sobre cada punto y
+// crece al alejarse, así que forma manchas redondeadas como cráteres o celdas.
+#[inline]
+fn worley(p: Vec3) -> f32 {
+    worley_distances(p).0
+}
+
+// Variante F2 - F1 del ruido celular: vale 0 en la frontera entre dos celdas y crece hacia el centro de cada una,
+// lo que da bordes afilados como facetas de cristal o grietas.
+#[inline]
+fn worley_edges(p: Vec3) -> f32 {
+    let (f1, f2) = worley_distances(p);
+    f2 - f1
+}
+
+// Suma `octaves` capas de ruido multifractal "con crestas": cada octava invierte el valor absoluto del ruido de
+// gradiente (1 - |n|) y lo eleva al cuadrado, así que los cruces por cero se vuelven crestas afiladas como las
+// de una cordillera. El resultado se normaliza por la suma de amplitudes y queda entre 0.0 y 1.0.
+#[inline]
+fn ridged_fbm(p: Vec3, octaves: i32) -> f32 {
     let mut sum = 0.0;
+    let mut total = 0.0;
     let mut freq = 1.0;
     let mut amp = 1.0;
     for _ in 0..octaves {
-        sum += amp * noise(p.x * freq, p.y * freq, p.z * freq).abs();
+        let ridge = 1.0 - perlin(p * freq).abs().min(1.0);
+        sum += amp * ridge * ridge;
+        total += amp;
         freq *= 2.0;
         amp *= 0.5;
     }
-    sum
+    if total > 0.0 { sum / total } else { 0.0 }
+}
+
+// Desplaza `p` según un campo vectorial de ruido (domain warping) antes de la consulta principal de ruido, para
+// que los patrones regulares se tuerzan y arremolinen. Cada componente del desplazamiento usa ruido de gradiente
+// en una región distinta del dominio; `strength` es el desplazamiento máximo aproximado.
+#[inline]
+fn domain_warp(p: Vec3, strength: f32) -> Vec3 {
+    let offset = Vec3::new(
+        perlin(p),
+        perlin(p + Vec3::new(31.4, 7.9, 12.6)),
+        perlin(p + Vec3::new(-15.2, 43.1, 5.7)),
+    );
+    p + offset * strength
+}
+
+// Genera turbulencia sumando múltiples capas de ruido (octavas) que duplican la frecuencia y reducen la amplitud a la mitad.
+#[inline]
+fn turbulence(p: Vec3, octaves: i32) -> f32 {
+    fbm(p, octaves, 2.0, 0.5)
+}
+
+// Paso de las diferencias finitas con que `bump_normal` estima el gradiente del relieve.
+const BUMP_EPSILON: f32 = 0.01;
+
+// Inclina la normal según el gradiente de un campo de alturas evaluado en `p`, para simular relieve sin
+// agregar triángulos. El gradiente se estima con diferencias finitas y solo se usa su parte tangente a la
+// superficie; `strength` controla qué tan marcado se ve el relieve (0.0 deja la normal intacta).
+// `p` está en el marco del cuerpo y `normal` en el mundo, así que el gradiente se lleva al mundo con `to_world`.
+fn bump_normal(normal: &Vec3, p: Vec3, to_world: &Mat3, strength: f32, height: impl Fn(Vec3) -> f32) -> Vec3 {
+    if strength == 0.0 {
+        return *normal;
+    }
+
+    let h = height(p);
+    let gradient = Vec3::new(
+        height(p + Vec3::new(BUMP_EPSILON, 0.0, 0.0)) - h,
+        height(p + Vec3::new(0.0, BUMP_EPSILON, 0.0)) - h,
+        height(p + Vec3::new(0.0, 0.0, BUMP_EPSILON)) - h,
+    ) / BUMP_EPSILON;
+    let gradient = to_world * gradient;
+    let tangential = gradient - normal * normal.dot(&gradient);
+
+    (normal - tangential * strength).normalize()
 }
 
 // --- SOMBREADOR PARA PLANETA ROCOSO ---
 
-pub struct RockyPlanet;
+pub struct RockyPlanet {
+    pub params: ShaderParams,
+    pub colors: [Vec3; 4],   // Montañas, tierra, costa y océano.
+    pub bump_strength: f32, // Intensidad del relieve de la tierra firme; los océanos quedan lisos.
+}
+
+impl Default for RockyPlanet {
+    fn default() -> Self {
+        RockyPlanet {
+            params: ShaderParams::default(),
+            colors: [
+                Vec3::new(0.7, 0.5, 0.3), // Montañas
+                Vec3::new(0.4, 0.6, 0.3), // Tierra
+                Vec3::new(0.8, 0.7, 0.5), // Costa
+                Vec3::new(0.1, 0.3, 0.6), // Océano
+            ],
+            bump_strength: 0.08,
+        }
+    }
+}
 
 impl PlanetShader for RockyPlanet {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, _time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal) = (&ctx.body_position, &ctx.normal);
         let normalized_pos = pos.normalize();
 
         // Define el color base según la altura (simulando montañas, tierra, costas y océanos).
         let height = normalized_pos.y;
+        let [mountains, land, coast, ocean] = self.colors;
         let base_color = if height > 0.4 {
-            Vec3::new(0.7, 0.5, 0.3) // Montañas
+            mountains
         } else if height > 0.0 {
-            Vec3::new(0.4, 0.6, 0.3) // Tierra
+            land
         } else if height > -0.3 {
-            Vec3::new(0.8, 0.7, 0.5) // Costa
+            coast
         } else {
-            Vec3::new(0.1, 0.3, 0.6) // Océano
+            ocean
         };
 
         // Agrega ruido para simular continentes y variaciones en el terreno.
-        let continent_noise = turbulence(normalized_pos * 3.0, 3);
+        let continent_noise = turbulence(self.params.sample(normalized_pos * 3.0), 3);
         let color_variation = mix_vec3(base_color, base_color * 0.8, continent_noise * 0.3);
 
         // Añade cráteres a la superficie.
@@ -80,45 +527,66 @@ impl PlanetShader for RockyPlanet {
         let crater_factor = smoothstep(0.85, 0.95, crater_pattern.abs());
         let crater_color = mix_vec3(color_variation, Vec3::new(0.3, 0.3, 0.35), crater_factor * 0.3);
 
+        // Da relieve a la tierra firme con cordilleras de ruido con crestas, más fino que el de los continentes.
+        let relief = if height > -0.3 { self.bump_strength } else { 0.0 };
+        let bumped = bump_normal(normal, normalized_pos, &ctx.body_to_world, relief, |p| ridged_fbm(self.params.sample(p * 4.0), 4));
+        let normal = &bumped;
+
         // Aplica iluminación difusa y especular (brillo en los océanos).
-        let light_dir = Vec3::new(1.0, 0.5, 1.0).normalize();
-        let diffuse = normal.dot(&light_dir).abs() * 0.6 + 0.4;
+        let material = &ctx.material;
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
         let specular = if height < 0.0 {
-            let view_dir = Vec3::new(0.0, 0.0, 1.0);
-            let half_vec = (light_dir + view_dir).normalize();
-            normal.dot(&half_vec).max(0.0).powf(32.0) * 0.4
+            accumulate_lights(ctx.lights, |i, l| material.specular_term(normal, l, &ctx.view_dir) * ctx.light_visibility(i))
         } else {
-            0.0
+            Vec3::zeros()
         };
 
-        let final_color = crater_color * diffuse + Vec3::new(1.0, 1.0, 1.0) * specular;
+        let final_color = crater_color.component_mul(&diffuse) + specular;
         Color::from_vec3(final_color)
     }
+
+    fn default_material(&self) -> Material {
+        Material { specular: 0.4, ..Material::default() }
+    }
 }
 
 // --- SOMBREADOR PARA GIGANTE GASEOSO ---
 
-pub struct GasGiant;
+pub struct GasGiant {
+    pub params: ShaderParams,
+    pub band_colors: [Vec3; 4], // Colores que se alternan entre las bandas de latitud.
+}
+
+impl Default for GasGiant {
+    fn default() -> Self {
+        GasGiant {
+            params: ShaderParams::default(),
+            band_colors: [
+                Vec3::new(0.9, 0.7, 0.5),
+                Vec3::new(0.8, 0.5, 0.3),
+                Vec3::new(0.7, 0.4, 0.2),
+                Vec3::new(0.6, 0.3, 0.2),
+            ],
+        }
+    }
+}
 
 impl PlanetShader for GasGiant {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
         let normalized_pos = pos.normalize();
 
-        // Crea bandas de colores basadas en la latitud.
-        let latitude = normalized_pos.y;
+        // Crea bandas de colores basadas en la latitud. La latitud se deforma con domain warping para que las
+        // bandas se arremolinen en vez de seguir paralelos rectos.
+        let warp_pos = self.params.sample(normalized_pos * 5.0);
+        let latitude = normalized_pos.y + (domain_warp(warp_pos, 0.6) - warp_pos).y * 0.07;
         let band_count = 12.0;
         let band = ((latitude + 1.0) * 0.5 * band_count).floor();
-        let band_colors = [
-            Vec3::new(0.9, 0.7, 0.5),
-            Vec3::new(0.8, 0.5, 0.3),
-            Vec3::new(0.7, 0.4, 0.2),
-            Vec3::new(0.6, 0.3, 0.2),
-        ];
-        let base_color = band_colors[band as usize % band_colors.len()];
+        let base_color = self.band_colors[band as usize % self.band_colors.len()];
 
         // Agrega turbulencia animada para simular la atmósfera gaseosa.
         let longitude = normalized_pos.z.atan2(normalized_pos.x) / (2.0 * PI);
-        let turb = noise(longitude * 8.0 + time * 0.3, latitude * 5.0, time * 0.1);
+        let turb = perlin(self.params.sample(Vec3::new(longitude * 8.0 + time * 0.3, latitude * 5.0, time * 0.1))) * 0.5 + 0.5;
         let turbulent_color = mix_vec3(base_color, base_color * 1.2, turb * 0.4);
 
         // Añade una "gran mancha roja" similar a la de Júpiter.
@@ -128,10 +596,9 @@ impl PlanetShader for GasGiant {
         let spot_color = Vec3::new(0.8, 0.2, 0.1);
         let color_with_spot = mix_vec3(turbulent_color, spot_color, spot_factor * 0.7);
 
-        // Aplica una iluminación suave para dar forma al planeta.
-        let light_dir = Vec3::new(1.0, 0.3, 1.0).normalize();
-        let terminator = smoothstep(0.0, 0.5, normal.dot(&light_dir).abs());
-        let final_color = color_with_spot * (0.3 + terminator * 0.7);
+        // Aplica una iluminación suave para dar forma al planeta. Los anillos le proyectan sombras a rayas.
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
+        let final_color = color_with_spot.component_mul(&diffuse);
 
         Color::from_vec3(final_color)
     }
@@ -139,21 +606,22 @@ impl PlanetShader for GasGiant {
 
 // --- SOMBREADOR PARA PLANETA CRISTALINO ---
 
-pub struct CrystalPlanet;
+#[derive(Default)]
+pub struct CrystalPlanet {
+    pub params: ShaderParams,
+}
 
 impl PlanetShader for CrystalPlanet {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
         let normalized_pos = pos.normalize();
 
-        // Crea un patrón geométrico hexagonal en la superficie.
-        let hex_x = normalized_pos.x * 8.0;
-        let hex_z = normalized_pos.z * 8.0;
-        let hex_pattern = ((hex_x * 2.0).sin() + (hex_z * 2.0).sin() + ((hex_x + hex_z) * 2.0).sin()) / 3.0;
-        let geo_factor = smoothstep(-0.1, 0.1, hex_pattern);
+        // Divide la superficie en facetas con ruido celular; las aristas entre facetas quedan oscuras.
+        let geo_factor = smoothstep(0.02, 0.12, worley_edges(self.params.sample(normalized_pos * 6.0)));
 
         // Genera un color iridiscente que cambia con la posición y el tiempo.
         let hue_shift = time * 0.5 + normalized_pos.y * 2.0;
-        let base_hue = (hue_shift % (2.0 * PI)) / (2.0 * PI);
+        let base_hue = hue_shift / (2.0 * PI); // `hsv_to_rgb` da la vuelta al tono, así que no hace falta acotarlo.
         let iridescent_color = hsv_to_rgb(base_hue, 0.7, 0.9);
 
         // Añade líneas de energía pulsantes.
@@ -162,8 +630,7 @@ impl PlanetShader for CrystalPlanet {
         let pulsing_color = iridescent_color * (1.0 + energy_lines);
 
         // Aplica un efecto Fresnel para que los bordes brillen.
-        let view_dir = Vec3::new(0.0, 0.0, 1.0);
-        let fresnel_power = fresnel(&view_dir, normal, 3.0);
+        let fresnel_power = fresnel(&ctx.view_dir, normal, 3.0);
         let fresnel_color = Vec3::new(0.8, 0.9, 1.0);
         let final_color = mix_vec3(pulsing_color * (0.5 + geo_factor * 0.5), fresnel_color, fresnel_power * 0.6);
 
@@ -171,101 +638,197 @@ impl PlanetShader for CrystalPlanet {
     }
 }
 
-// Convierte un color de formato HSV (Tono, Saturación, Valor) a RGB.
+// Convierte un color de formato HSV (Tono, Saturación, Valor) a RGB. El tono es cíclico: se toma módulo 1.0,
+// así que 1.0 y los valores negativos dan el mismo color que su equivalente en [0, 1). Con saturación cero
+// (o negativa) el resultado es el gris de valor `v`.
 fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Vec3 {
+    if s <= 0.0 {
+        return Vec3::repeat(v);
+    }
+
+    // `rem_euclid` nunca es negativo, a diferencia de `%`. Por redondeo puede devolver justo 6.0 para un tono
+    // apenas negativo; el módulo del sector lo lleva de vuelta al rojo.
+    let sector = h.rem_euclid(1.0) * 6.0;
     let c = v * s;
-    let x = c * (1.0 - ((h * 6.0) % 2.0 - 1.0).abs());
+    let x = c * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
     let m = v - c;
 
-    let (r, g, b) = if h < 1.0 / 6.0 {
-        (c, x, 0.0)
-    } else if h < 2.0 / 6.0 {
-        (x, c, 0.0)
-    } else if h < 3.0 / 6.0 {
-        (0.0, c, x)
-    } else if h < 4.0 / 6.0 {
-        (0.0, x, c)
-    } else if h < 5.0 / 6.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
+    let (r, g, b) = match sector as usize % 6 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
     };
 
     Vec3::new(r + m, g + m, b + m)
 }
 
+// Convierte un color RGB (cada canal entre 0.0 y 1.0) a HSV, devuelto como (tono, saturación, valor) con el
+// tono en [0, 1). Es la inversa de `hsv_to_rgb`. En los grises el tono no está definido y se devuelve 0.0,
+// igual que la saturación. Sirve para ajustar tono o saturación en una pasada de corrección de color.
+#[cfg_attr(not(test), allow(dead_code))]
+fn rgb_to_hsv(c: Vec3) -> Vec3 {
+    let max = c.max();
+    let min = c.min();
+    let delta = max - min;
+    if max <= 0.0 || delta <= 0.0 {
+        return Vec3::new(0.0, 0.0, max);
+    }
+
+    // El tono depende de qué canal es el mayor; cada caso cubre dos sextos del círculo.
+    let sector = if max == c.x {
+        (c.y - c.z) / delta
+    } else if max == c.y {
+        (c.z - c.x) / delta + 2.0
+    } else {
+        (c.x - c.y) / delta + 4.0
+    };
+
+    Vec3::new((sector / 6.0).rem_euclid(1.0), delta / max, max)
+}
+
 // --- SOMBREADOR PARA PLANETA DE LAVA ---
 
-pub struct LavaPlanet;
+pub struct LavaPlanet {
+    pub params: ShaderParams,
+    pub lava_colors: [Vec3; 2], // Lava más fría y más caliente; el brillo pulsa entre ambas.
+    pub rock_color: Vec3,       // Roca solidificada entre las grietas.
+}
+
+impl Default for LavaPlanet {
+    fn default() -> Self {
+        LavaPlanet {
+            params: ShaderParams::default(),
+            lava_colors: [Vec3::new(1.0, 0.3, 0.0), Vec3::new(1.0, 0.8, 0.0)],
+            rock_color: Vec3::new(0.15, 0.1, 0.08),
+        }
+    }
+}
 
 impl PlanetShader for LavaPlanet {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
         let normalized_pos = pos.normalize();
 
-        // Crea un patrón de grietas animadas en la superficie.
-        let crack_pattern = turbulence(normalized_pos * 5.0, 3);
-        let animated_crack = crack_pattern + (time * 0.5).sin() * 0.3;
-        let is_lava = animated_crack > 0.6;
+        // Crea un patrón de grietas animadas en la superficie. Las grietas siguen las fronteras entre las
+        // celdas del ruido celular, que forman una red continua de placas de roca.
+        let crack_pattern = 1.0 - (worley_edges(self.params.sample(normalized_pos * 5.0)) * 2.5).min(1.0);
+        let animated_crack = crack_pattern + (time * 0.5).sin() * 0.1;
+        let is_lava = animated_crack > 0.8;
 
         // Define el color base: lava brillante o roca oscura.
         let base_color = if is_lava {
             let intensity = (time * 4.0 + crack_pattern * 10.0).sin() * 0.5 + 0.5;
-            mix_vec3(Vec3::new(1.0, 0.3, 0.0), Vec3::new(1.0, 0.8, 0.0), intensity)
+            mix_vec3(self.lava_colors[0], self.lava_colors[1], intensity)
         } else {
-            Vec3::new(0.15, 0.1, 0.08)
+            self.rock_color
         };
 
-        // La lava emite su propia luz, mientras que la roca se ilumina de forma difusa.
-        let light_dir = Vec3::new(1.0, 1.0, 1.0).normalize();
-        let diffuse = normal.dot(&light_dir).abs() * 0.5 + 0.5;
+        // La lava emite su propia luz, mientras que la roca se ilumina de forma difusa y queda a oscuras donde
+        // la luna la eclipsa.
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
 
         let final_color = if is_lava {
             base_color * 1.5 // La lava es más brillante.
         } else {
-            base_color * diffuse
+            base_color.component_mul(&diffuse)
         };
 
         Color::from_vec3(final_color)
     }
+
+    fn default_material(&self) -> Material {
+        Material { ambient: 0.25, ..Material::default() }
+    }
 }
 
 // --- SOMBREADOR PARA MUNDO CONGELADO ---
 
-pub struct IcePlanet;
+pub struct IcePlanet {
+    pub params: ShaderParams,
+    pub colors: [Vec3; 2], // Hielo claro y cristales más oscuros.
+}
+
+impl Default for IcePlanet {
+    fn default() -> Self {
+        IcePlanet {
+            params: ShaderParams::default(),
+            colors: [Vec3::new(0.7, 0.8, 0.95), Vec3::new(0.5, 0.6, 0.8)],
+        }
+    }
+}
 
 impl PlanetShader for IcePlanet {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, _time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal) = (&ctx.body_position, &ctx.normal);
         let normalized_pos = pos.normalize();
 
         // Crea un patrón de hielo y cristales usando turbulencia.
-        let ice_pattern = turbulence(normalized_pos * 10.0, 4);
+        let ice_pattern = turbulence(self.params.sample(normalized_pos * 10.0), 4);
         let crystal_factor = smoothstep(0.4, 0.6, ice_pattern);
-        let base_color = mix_vec3(Vec3::new(0.7, 0.8, 0.95), Vec3::new(0.5, 0.6, 0.8), crystal_factor);
+        let base_color = mix_vec3(self.colors[0], self.colors[1], crystal_factor);
 
-        // Aplica iluminación difusa y un fuerte brillo especular para simular el hielo.
-        let light_dir = Vec3::new(1.0, 1.0, 1.0).normalize();
-        let diffuse = normal.dot(&light_dir).abs() * 0.5 + 0.5;
-        let view_dir = Vec3::new(0.0, 0.0, 1.0);
-        let half_vec = (light_dir + view_dir).normalize();
-        let specular = normal.dot(&half_vec).max(0.0).powf(64.0);
-        let final_color = base_color * diffuse + Vec3::new(1.0, 1.0, 1.0) * specular * 0.8;
+        // Aplica iluminación difusa y un fuerte brillo especular para simular el hielo. Los dos se apagan en la
+        // sombra de la luna.
+        let material = &ctx.material;
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
+        let specular = accumulate_lights(ctx.lights, |i, l| {
+            material.specular_term(normal, l, &ctx.view_dir) * ctx.light_visibility(i)
+        });
+        let final_color = base_color.component_mul(&diffuse) + specular;
 
         Color::from_vec3(final_color)
     }
+
+    fn default_material(&self) -> Material {
+        Material {
+            ambient: 0.2,
+            specular: 0.8,
+            shininess: 64.0,
+            ..Material::default()
+        }
+    }
 }
 
 // --- SOMBREADOR PARA ANILLOS ---
 
+// Radios interior y exterior de la malla del anillo con que se diseñó `RingShader`.
+pub const RING_RADII: [f32; 2] = [1.3, 2.0];
+
+// Densidad de partículas del anillo (0.0-1.0) a la distancia `dist` del centro, en unidades de la malla: las
+// bandas claras son más densas que las oscuras, anillos finos la hacen variar y una división vacía, como la de
+// Cassini, separa el anillo en dos. Los bordes se desvanecen dentro de la malla en vez de cortarse. Es la opacidad
+// con que se dibuja el anillo y también la fracción de luz que detiene su sombra.
+fn ring_density(dist: f32) -> f32 {
+    let band = (dist * RING_BAND_COUNT).floor();
+    let band_density = if band as i32 % 2 == 0 { 0.9 } else { 0.7 };
+    let ringlets = 0.8 + ((dist * 90.0).sin() * 0.5 + 0.5) * 0.2;
+    let division = smoothstep(0.015, 0.04, (dist - 1.72).abs());
+
+    let [inner, outer] = RING_RADII;
+    let alpha_inner = smoothstep(inner, inner + 0.1, dist);
+    let alpha_outer = smoothstep(outer, outer - 0.12, dist);
+    band_density * ringlets * division * alpha_inner * alpha_outer
+}
+
+// Número de bandas de color por unidad de radio.
+const RING_BAND_COUNT: f32 = 15.0;
+
 pub struct RingShader;
 
 impl PlanetShader for RingShader {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        self.fragment_with_alpha(ctx).0
+    }
+
+    fn fragment_with_alpha(&self, ctx: &FragmentContext) -> (Color, f32) {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
         let dist_from_center = (pos.x * pos.x + pos.z * pos.z).sqrt();
 
         // Crea bandas de colores alternos en el anillo.
-        let band_count = 15.0;
-        let band = (dist_from_center * band_count).floor();
-        let _band_mix = (dist_from_center * band_count).fract();
+        let band = (dist_from_center * RING_BAND_COUNT).floor();
 
         // Colores alternados para las bandas
         let color1 = Vec3::new(0.8, 0.7, 0.6);
@@ -281,47 +844,581 @@ impl PlanetShader for RingShader {
         let color_with_noise = base_color * (0.8 + noise_val * 0.4);
 
         // Aplica iluminación simple y transparencia en los bordes del anillo.
-        let light_dir = Vec3::new(1.0, 0.5, 1.0).normalize();
-        let n_dot_l = normal.dot(&light_dir).abs();
-        let lit_color = color_with_noise * (0.5 + n_dot_l * 0.5);
-
-        // Transparencia en los bordes
-        let alpha_inner = smoothstep(0.0, 0.05, dist_from_center - 1.3);
-        let alpha_outer = smoothstep(2.2, 2.0, dist_from_center);
-        let alpha = alpha_inner * alpha_outer;
-
-        // Simula la transparencia devolviendo un color oscuro si el alfa es bajo.
-        if alpha < 0.3 {
-            Color::BLACK
-        } else {
-            Color::from_vec3(lit_color * alpha)
-        }
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
+        let lit_color = color_with_noise.component_mul(&diffuse);
+
+        // El renderizador mezcla el color con lo que hay detrás según la densidad de partículas; el planeta se ve
+        // a través de las partes delgadas.
+        (Color::from_vec3(lit_color), ring_density(dist_from_center))
+    }
+
+    fn default_material(&self) -> Material {
+        Material { ambient: 0.3, ..Material::default() }
+    }
+
+    fn is_ring(&self) -> bool {
+        true
     }
 }
 
 // --- SOMBREADOR PARA LA LUNA ---
 
-pub struct MoonShader;
+pub struct MoonShader {
+    pub params: ShaderParams,
+    pub colors: [Vec3; 2],  // Superficie y fondo de los cráteres.
+    pub bump_strength: f32, // Intensidad del relieve de los cráteres.
+}
+
+impl Default for MoonShader {
+    fn default() -> Self {
+        MoonShader {
+            params: ShaderParams::default(),
+            colors: [Vec3::new(0.4, 0.4, 0.45), Vec3::new(0.25, 0.25, 0.28)],
+            bump_strength: 0.06,
+        }
+    }
+}
 
 impl PlanetShader for MoonShader {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, _time: f32) -> Color {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal) = (&ctx.body_position, &ctx.normal);
         let normalized_pos = pos.normalize();
 
-        // Crea una superficie rocosa con cráteres.
-        let crater_noise = turbulence(normalized_pos * 8.0, 3);
-        let crater = smoothstep(0.6, 0.8, crater_noise);
-        let base_color = Vec3::new(0.4, 0.4, 0.45);
-        let crater_color = Vec3::new(0.25, 0.25, 0.28);
-        let surface_color = mix_vec3(base_color, crater_color, crater * 0.6);
+        // Crea una superficie rocosa con cráteres: cada punto del ruido celular es el centro de uno, así que la
+        // altura es 0 en el fondo y sube hasta 1 en la llanura que los rodea.
+        let crater_height = |p: Vec3| smoothstep(0.0, 0.45, worley(self.params.sample(p * 8.0)));
+        let crater = 1.0 - crater_height(normalized_pos);
+        let surface_color = mix_vec3(self.colors[0], self.colors[1], crater * 0.6);
 
         // Agrega una textura de ruido fino para dar detalle a la superficie.
-        let detail = noise(normalized_pos.x * 30.0, normalized_pos.y * 30.0, normalized_pos.z * 30.0);
+        let detail_pos = self.params.sample(normalized_pos * 30.0);
+        let detail = noise(detail_pos.x, detail_pos.y, detail_pos.z);
         let detailed_color = surface_color * (0.9 + detail * 0.2);
 
+        // Los cráteres hunden la superficie: la misma altura sirve de campo para el relieve.
+        let bumped = bump_normal(normal, normalized_pos, &ctx.body_to_world, self.bump_strength, crater_height);
+
         // Aplica iluminación difusa para dar forma a la luna.
-        let light_dir = Vec3::new(1.0, 0.5, 1.0).normalize();
-        let diffuse = normal.dot(&light_dir).abs() * 0.7 + 0.3;
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(&bumped, i));
+
+        Color::from_vec3(detailed_color.component_mul(&diffuse))
+    }
+
+    fn default_material(&self) -> Material {
+        Material { ambient: 0.1, ..Material::default() }
+    }
+}
+
+// --- SOMBREADOR PARA PLANETA TIPO TIERRA ---
+
+#[derive(Default)]
+pub struct EarthPlanet {
+    pub params: ShaderParams,
+}
+
+impl PlanetShader for EarthPlanet {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal) = (&ctx.body_position, &ctx.normal);
+        let normalized_pos = pos.normalize();
+        let latitude = normalized_pos.y.abs();
+
+        // Ondas de baja frecuencia dan la forma general de los continentes y la turbulencia recorta las costas.
+        let shape = (normalized_pos.x * 2.3 + 0.7).sin() * (normalized_pos.z * 1.9 - 0.4).cos()
+            + (normalized_pos.y * 2.7 + normalized_pos.x * 1.3).sin() * 0.5;
+        let elevation = shape * 0.6 + turbulence(self.params.sample(normalized_pos * 4.0), 4) * 0.35 - 0.25;
+        let land = smoothstep(-0.02, 0.02, elevation); // Costa suave entre océano y tierra.
+
+        // Océano más claro en las aguas poco profundas cerca de la costa.
+        let ocean_color = mix_vec3(Vec3::new(0.02, 0.1, 0.35), Vec3::new(0.05, 0.3, 0.55), smoothstep(-0.3, 0.0, elevation));
+
+        // La tierra pasa de verde a marrón con la altura; hacia los polos se comporta como si fuera más alta.
+        let land_height = elevation + latitude * 0.3;
+        let land_color = mix_vec3(Vec3::new(0.2, 0.5, 0.15), Vec3::new(0.5, 0.4, 0.25), smoothstep(0.1, 0.5, land_height));
+        let surface = mix_vec3(ocean_color, land_color, land);
+
+        // Casquetes polares con un borde irregular.
+        let ice = smoothstep(0.78, 0.82, latitude + turbulence(self.params.sample(normalized_pos * 6.0), 2) * 0.05);
+        let surface = mix_vec3(surface, Vec3::new(0.95, 0.97, 1.0), ice);
+
+        // Iluminación difusa con un lado nocturno oscuro y reflejo del sol solo sobre el agua.
+        let material = &ctx.material;
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
+        let water = (1.0 - land) * (1.0 - ice);
+        let specular = accumulate_lights(ctx.lights, |i, l| {
+            material.specular_term(normal, l, &ctx.view_dir) * ctx.light_visibility(i)
+        }) * water;
+
+        // Luces de ciudades en el lado nocturno, solo sobre tierra sin hielo. Se agrupan en regiones
+        // pobladas y aparecen de forma gradual al cruzar el terminador de la luz principal.
+        let sun_dot = ctx.lights.first().map_or(-1.0, |light| normal.dot(&light.dir));
+        let night = smoothstep(0.1, -0.15, sun_dot);
+        let populated = smoothstep(0.5, 0.8, turbulence(self.params.sample(normalized_pos * 7.0 + Vec3::repeat(3.1)), 3));
+        let sparkle_pos = self.params.sample(normalized_pos * 300.0);
+        let sparkle = noise(sparkle_pos.x, sparkle_pos.y, sparkle_pos.z);
+        let city = smoothstep(0.8, 0.95, sparkle) * populated * land * (1.0 - ice);
+        let city_lights = Vec3::new(1.0, 0.65, 0.25) * (city * night);
+
+        Color::from_vec3(surface.component_mul(&diffuse) + specular + city_lights)
+    }
+
+    fn default_material(&self) -> Material {
+        Material {
+            specular: 0.6,
+            shininess: 64.0,
+            ..Material::default()
+        }
+    }
+}
+
+// --- SOMBREADOR PARA ESTRELLAS ---
+
+// Estrella que emite su propia luz: no usa las luces de la escena.
+pub struct StarShader;
+
+impl PlanetShader for StarShader {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
+        let normalized_pos = pos.normalize();
+
+        // Granulación: celdas de convección que cambian lentamente con el tiempo.
+        let granulation = turbulence(normalized_pos * 12.0 + Vec3::new(time * 0.2, time * 0.15, -time * 0.1), 4);
+        let cells = smoothstep(0.3, 0.9, granulation);
+
+        // Oscurecimiento hacia el limbo: el borde del disco se ve más frío y tenue que el centro.
+        let limb = fresnel(&ctx.view_dir, normal, 1.5);
+        let core_color = Vec3::new(1.0, 0.95, 0.75);
+        let edge_color = Vec3::new(1.0, 0.5, 0.1);
+        let base_color = mix_vec3(core_color, edge_color, limb);
+
+        // Las celdas más calientes aclaran el color; el brillo total se mantiene en [0, 1].
+        let brightness = (0.8 + cells * 0.2) * (1.0 - limb * 0.35);
+        Color::from_vec3(base_color * brightness)
+    }
+}
+
+// --- SOMBREADOR PARA PLANETA DESÉRTICO ---
+
+#[derive(Default)]
+pub struct DesertPlanet {
+    pub params: ShaderParams,
+}
+
+impl PlanetShader for DesertPlanet {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
+        let normalized_pos = pos.normalize();
+
+        // Dunas: varias ondas sinusoidales en direcciones distintas, deformadas por turbulencia.
+        // El ruido depende solo de la posición, así que las dunas no parpadean entre cuadros.
+        let warp = turbulence(self.params.sample(normalized_pos * 3.0), 3) * 1.5;
+        let dunes = ((normalized_pos.x * 9.0 + normalized_pos.z * 4.0 + warp).sin() * 0.5
+            + (normalized_pos.z * 13.0 - normalized_pos.y * 5.0 + warp * 0.7).sin() * 0.3
+            + (normalized_pos.y * 17.0 + normalized_pos.x * 6.0).sin() * 0.2)
+            * 0.5
+            + 0.5;
+
+        // Ondulaciones finas del viento que se desplazan lentamente con el tiempo.
+        let ripples = ((normalized_pos.x + normalized_pos.z) * 80.0 + dunes * 6.0 - time * 0.5).sin() * 0.5 + 0.5;
+
+        // Arena de tonos canela en los valles a ocre en las crestas.
+        let tan = Vec3::new(0.85, 0.72, 0.5);
+        let ochre = Vec3::new(0.75, 0.5, 0.22);
+        let sand = mix_vec3(tan, ochre, smoothstep(0.3, 0.8, dunes)) * (0.95 + ripples * 0.05);
+
+        // Iluminación difusa con un ambiente cálido tenue. El ambiente se suma una sola vez, teñido,
+        // en vez de por cada luz como en `Material::diffuse_term`.
+        let material = &ctx.material;
+        let ambient = Vec3::new(1.0, 0.72, 0.48) * material.ambient;
+        let diffuse = ambient
+            + accumulate_lights(ctx.lights, |i, l| lambert(normal, l, 0.0) * material.diffuse * ctx.light_visibility(i));
+
+        Color::from_vec3(sand.component_mul(&diffuse))
+    }
+
+    fn default_material(&self) -> Material {
+        Material {
+            ambient: 0.25,
+            diffuse: 0.8,
+            ..Material::default()
+        }
+    }
+}
+
+// --- SOMBREADOR PARA PLANETA OCEÁNICO ---
+
+pub struct OceanPlanet;
+
+impl OceanPlanet {
+    const WAVE_SPEED: f32 = 1.5; // Velocidad de avance de las olas.
+    const CHOPPINESS: f32 = 0.08; // Cuánto inclinan las olas la normal de la superficie.
+
+    // Olas como (dirección, frecuencia, amplitud).
+    const WAVES: [([f32; 3], f32, f32); 4] = [
+        ([1.0, 0.2, 0.3], 18.0, 1.0),
+        ([-0.4, 0.3, 1.0], 27.0, 0.6),
+        ([0.7, -0.5, -0.6], 41.0, 0.35),
+        ([-0.8, 0.6, 0.1], 63.0, 0.2),
+    ];
+}
+
+impl PlanetShader for OceanPlanet {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let (pos, normal, time) = (&ctx.body_position, &ctx.normal, ctx.time);
+        let normalized_pos = pos.normalize();
+
+        // Gradiente de la altura de las olas: cada ola es sin(freq * dir·p + velocidad * t) y aporta cos(...) * freq * dir.
+        let mut gradient = Vec3::zeros();
+        for (dir, freq, amplitude) in Self::WAVES {
+            let dir = Vec3::new(dir[0], dir[1], dir[2]).normalize();
+            let phase = normalized_pos.dot(&dir) * freq + time * Self::WAVE_SPEED * (freq / 18.0).sqrt();
+            gradient += dir * (phase.cos() * amplitude);
+        }
+        // Un poco de ruido rompe la regularidad de las ondas.
+        gradient += Vec3::new(
+            noise(normalized_pos.x * 40.0, time * 0.5, normalized_pos.z * 40.0) - 0.5,
+            0.0,
+            noise(normalized_pos.z * 40.0, normalized_pos.y * 40.0, time * 0.5) - 0.5,
+        ) * 0.5;
+
+        // Solo la parte del gradiente tangente a la superficie inclina la normal.
+        let tangent_gradient = gradient - normal * normal.dot(&gradient);
+        let wave_normal = (normal - tangent_gradient * Self::CHOPPINESS).normalize();
+        let slope = (tangent_gradient.magnitude() * Self::CHOPPINESS * 4.0).min(1.0);
+
+        // Azul profundo con espuma clara donde la pendiente de las olas es fuerte.
+        let deep = Vec3::new(0.01, 0.08, 0.3);
+        let shallow = Vec3::new(0.05, 0.25, 0.5);
+        let water = mix_vec3(deep, shallow, fresnel(&ctx.view_dir, &wave_normal, 2.0));
+        let foam = smoothstep(0.55, 0.9, slope);
+        let surface = mix_vec3(water, Vec3::new(0.9, 0.95, 1.0), foam * 0.7);
+
+        // Difusa con la normal de las olas y un reflejo del sol fuerte y concentrado.
+        let material = &ctx.material;
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(&wave_normal, i));
+        let specular = accumulate_lights(ctx.lights, |i, l| {
+            material.specular_term(&wave_normal, l, &ctx.view_dir) * ctx.light_visibility(i)
+        });
+
+        Color::from_vec3(surface.component_mul(&diffuse) + specular * (1.0 - foam))
+    }
+
+    fn default_material(&self) -> Material {
+        Material {
+            specular: 1.2,
+            shininess: 128.0,
+            ..Material::default()
+        }
+    }
+}
+
+// --- SOMBREADOR PARA CAPA DE NUBES ---
+
+// Capa de nubes translúcida pensada para una esfera un poco más grande (escala ~1.02) que el planeta.
+// Las zonas sin nubes son completamente transparentes.
+#[derive(Default)]
+pub struct CloudShader {
+    pub params: ShaderParams,
+}
+
+impl CloudShader {
+    // Devuelve la cobertura de nubes (0.0-1.0) en un punto de la esfera unitaria.
+    fn coverage(&self, normalized_pos: &Vec3, time: f32) -> f32 {
+        // Las nubes se desplazan en longitud y se deforman lentamente con el tiempo.
+        let drift = Vec3::new(time * 0.03, 0.0, -time * 0.02);
+        let p = normalized_pos + drift;
+        let bands = (p.y * 6.0 + (p.x * 3.0 + time * 0.1).sin() * 1.5).sin() * 0.5 + 0.5;
+        // Más ganancia que la turbulencia normal para que los bordes de las nubes sean más deshilachados.
+        let detail = fbm(self.params.sample(p * 5.0), 4, 2.2, 0.6) * 0.85;
+        smoothstep(0.55, 0.85, bands * 0.6 + detail * 0.5)
+    }
+}
+
+impl PlanetShader for CloudShader {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        self.fragment_with_alpha(ctx).0
+    }
+
+    fn fragment_with_alpha(&self, ctx: &FragmentContext) -> (Color, f32) {
+        let normal = &ctx.normal;
+        let coverage = self.coverage(&ctx.body_position.normalize(), ctx.time);
+
+        // Nubes blancas con iluminación difusa suave; el lado nocturno queda gris oscuro.
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
+        let color = Vec3::new(0.95, 0.95, 0.97).component_mul(&diffuse);
 
-        Color::from_vec3(detailed_color * diffuse)
+        (Color::from_vec3(color), coverage * 0.9)
     }
-}
\ No newline at end of file
+
+    fn default_material(&self) -> Material {
+        Material { ambient: 0.2, ..Material::default() }
+    }
+}
+
+// --- SOMBREADOR PARA ATMÓSFERA ---
+
+// Halo azulado para una capa un poco más grande que el planeta de la que se dibujan solo las caras traseras.
+// Es más intenso donde la vista roza la capa (Fresnel) y transparente hacia el centro del disco.
+pub struct AtmosphereShader;
+
+impl AtmosphereShader {
+    const FRESNEL_POWER: f32 = 2.0; // Exponente del término de Fresnel; más alto concentra el halo en el borde.
+    const STRENGTH: f32 = 1.5; // Opacidad máxima aproximada del halo.
+}
+
+impl PlanetShader for AtmosphereShader {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        self.fragment_with_alpha(ctx).0
+    }
+
+    fn fragment_with_alpha(&self, ctx: &FragmentContext) -> (Color, f32) {
+        let (pos, normal) = (&ctx.body_position, &ctx.normal);
+
+        // Intensidad del borde; en el mismo borde de la capa se desvanece para no dejar una línea dura.
+        let rim = fresnel(&ctx.view_dir, normal, Self::FRESNEL_POWER);
+        let edge_fade = smoothstep(1.0, 0.7, rim);
+
+        // El lado iluminado del halo brilla más. Se usa la posición y no la normal,
+        // porque en las caras traseras la normal apunta en sentido contrario a la cámara.
+        let up = (ctx.body_to_world * pos).normalize();
+        let daylight = accumulate_lights(ctx.lights, |_, l| smoothstep(-0.3, 0.5, up.dot(l)));
+        let color = Vec3::new(0.35, 0.6, 1.0).component_mul(&(Vec3::repeat(0.25) + daylight * 0.75));
+
+        let alpha = (rim * edge_fade * Self::STRENGTH).clamp(0.0, 1.0);
+        (Color::from_vec3(color), alpha)
+    }
+}
+
+// --- SOMBREADOR CON TEXTURA ---
+
+// Define de dónde salen las coordenadas con que se muestrea una textura.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureMapping {
+    MeshUv,          // Las coordenadas UV de la malla.
+    Equirectangular, // Longitud y latitud de la posición en el objeto, para mapas planetarios 2:1.
+}
+
+// Colorea el objeto con una de las texturas del renderizador.
+// Si el índice no corresponde a ninguna textura cargada, el objeto se dibuja gris.
+pub struct TexturedShader {
+    pub texture: usize,          // Índice de la textura en `FragmentContext::textures`.
+    pub mapping: TextureMapping, // Cómo se calculan las coordenadas de textura.
+}
+
+impl TexturedShader {
+    // Convierte un punto en el espacio del objeto en coordenadas de un mapa equirectangular: U recorre la
+    // longitud y V va del polo norte (0) al polo sur (1). Se calcula por píxel, así que no depende de la
+    // costura de UV de la malla; U se envuelve al muestrear y V se mantiene dentro de la imagen en los polos.
+    fn equirectangular_uv(local_position: &Vec3) -> Vec2 {
+        let p = local_position.normalize();
+        let u = p.z.atan2(p.x) / (2.0 * PI) + 0.5;
+        let v = p.y.clamp(-1.0, 1.0).acos() / PI;
+        Vec2::new(u, v.min(1.0 - f32::EPSILON))
+    }
+}
+
+impl PlanetShader for TexturedShader {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let uv = match self.mapping {
+            TextureMapping::MeshUv => ctx.uv,
+            TextureMapping::Equirectangular => Self::equirectangular_uv(&ctx.local_position),
+        };
+        let base_color = ctx
+            .textures
+            .get(self.texture)
+            .map_or(Vec3::repeat(0.5), |texture| texture.sample(uv).to_vec3());
+
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(&ctx.normal, i));
+        Color::from_vec3(base_color.component_mul(&diffuse))
+    }
+}
+
+// --- SOMBREADOR DE COLOR SÓLIDO ---
+
+// Ilumina la malla con los colores de su material (.mtl), para modelos que no necesitan un sombreador propio.
+// El exponente especular del .mtl pasa al material por defecto, así que se puede cambiar desde la escena.
+pub struct SolidShader {
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub shininess: f32,
+}
+
+impl SolidShader {
+    // Usa el material dado o, si no hay, un gris mate.
+    pub fn from_material(material: Option<&mesh::Material>) -> Self {
+        match material {
+            Some(material) => SolidShader {
+                diffuse: material.diffuse,
+                specular: material.specular,
+                shininess: material.shininess,
+            },
+            None => SolidShader {
+                diffuse: Vec3::repeat(0.8),
+                specular: Vec3::zeros(),
+                shininess: 0.0,
+            },
+        }
+    }
+}
+
+impl PlanetShader for SolidShader {
+    fn fragment(&self, ctx: &FragmentContext) -> Color {
+        let normal = &ctx.normal;
+
+        // Difusa con un término ambiental pequeño para que el lado oscuro no quede negro.
+        let material = &ctx.material;
+        let diffuse = accumulate_lights(ctx.lights, |i, _| ctx.shadowed_diffuse(normal, i));
+        let mut final_color = self.diffuse.component_mul(&diffuse);
+
+        // Blinn-Phong solo si el material define un brillo especular.
+        if material.shininess > 0.0 && self.specular != Vec3::zeros() {
+            let specular = accumulate_lights(ctx.lights, |i, l| {
+                material.specular_term(normal, l, &ctx.view_dir) * ctx.light_visibility(i)
+            });
+            final_color += self.specular.component_mul(&specular);
+        }
+
+        Color::from_vec3(final_color)
+    }
+
+    fn default_material(&self) -> Material {
+        Material {
+            specular: 1.0,
+            shininess: self.shininess,
+            ..Material::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Puntos repartidos de forma irregular por varias celdas de la rejilla del ruido, incluidas coordenadas negativas.
+    fn sample_points() -> impl Iterator<Item = Vec3> {
+        (0..500).map(|i| {
+            let t = i as f32;
+            Vec3::new((t * 0.37).sin() * 7.3, (t * 0.61).cos() * 5.1 + t * 0.013, t * 0.029 - 6.0)
+        })
+    }
+
+    #[test]
+    fn fbm_is_continuous() {
+        // Un paso pequeño cambia poco el valor, también al cruzar los bordes de las celdas.
+        let step = Vec3::repeat(1e-3);
+        for p in sample_points().chain((-3..=3).map(|i| Vec3::repeat(i as f32) - step * 0.5)) {
+            let (a, b) = (fbm(p, 5, 2.0, 0.5), fbm(p + step, 5, 2.0, 0.5));
+            assert!((a - b).abs() < 0.05, "fbm({p:?}) = {a}, fbm(p + step) = {b}");
+            assert!(a >= 0.0);
+        }
+    }
+
+    #[test]
+    fn perlin_is_zero_at_lattice_points() {
+        for x in -3..=3 {
+            for y in -3..=3 {
+                for z in -3..=3 {
+                    let value = perlin(Vec3::new(x as f32, y as f32, z as f32));
+                    assert!(value.abs() < 1e-6, "perlin({x}, {y}, {z}) = {value}");
+                }
+            }
+        }
+        // Entre los puntos de la rejilla el ruido no se anula en todas partes.
+        assert!(sample_points().any(|p| perlin(p).abs() > 0.1));
+    }
+
+    #[test]
+    fn worley_is_zero_at_a_feature_point_and_grows_away_from_it() {
+        // El punto característico de la celda (1, 2, 3), calculado igual que en `worley_distances`.
+        let feature = Vec3::new(1.0 + lattice_hash(1, 2, 3), 2.0 + lattice_hash(2, 3, 1), 3.0 + lattice_hash(3, 1, 2));
+        assert!(worley(feature) < 1e-6);
+
+        // Cerca del punto, la distancia al más cercano es la distancia a él.
+        let direction = Vec3::new(0.3, -0.5, 0.8).normalize();
+        let mut previous = 0.0;
+        for distance in [0.01, 0.02, 0.04, 0.08] {
+            let value = worley(feature + direction * distance);
+            assert!(value > previous);
+            assert!((value - distance).abs() < 1e-4, "{value} != {distance}");
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn ridged_fbm_and_domain_warp_stay_in_range() {
+        for p in sample_points() {
+            let ridged = ridged_fbm(p, 5);
+            assert!((0.0..=1.0).contains(&ridged), "ridged_fbm({p:?}) = {ridged}");
+
+            // El desplazamiento no pasa de `strength` en cada eje, y el ruido consultado en el punto desplazado
+            // sigue en [0, 1].
+            let warped = domain_warp(p, 0.5);
+            assert!((warped - p).amax() <= 0.5 * 1.05, "{p:?} -> {warped:?}");
+            let value = ridged_fbm(warped, 4);
+            assert!((0.0..=1.0).contains(&value), "ridged_fbm(domain_warp({p:?})) = {value}");
+        }
+    }
+
+    #[test]
+    fn hsv_to_rgb_primary_hues() {
+        let cases = [
+            (0.0, Vec3::new(1.0, 0.0, 0.0)),
+            (1.0 / 6.0, Vec3::new(1.0, 1.0, 0.0)),
+            (0.5, Vec3::new(0.0, 1.0, 1.0)),
+            (1.0, Vec3::new(1.0, 0.0, 0.0)), // El tono es cíclico: 1.0 vuelve al rojo.
+        ];
+        for (hue, expected) in cases {
+            let rgb = hsv_to_rgb(hue, 1.0, 1.0);
+            assert!((rgb - expected).amax() < 1e-5, "hue {hue}: {rgb:?} != {expected:?}");
+        }
+
+        // Sin saturación, el resultado es el gris del valor.
+        assert_eq!(hsv_to_rgb(0.3, 0.0, 0.25), Vec3::repeat(0.25));
+    }
+
+    #[test]
+    fn rgb_to_hsv_round_trips() {
+        let colors = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.2, 0.6, 0.4),
+            Vec3::new(0.9, 0.8, 0.1),
+            Vec3::new(0.3, 0.1, 0.7),
+            Vec3::new(0.05, 0.5, 0.95),
+            Vec3::new(0.8, 0.2, 0.6),
+        ];
+        for color in colors {
+            let hsv = rgb_to_hsv(color);
+            let rgb = hsv_to_rgb(hsv.x, hsv.y, hsv.z);
+            assert!((rgb - color).amax() < 1e-5, "{color:?} -> {hsv:?} -> {rgb:?}");
+        }
+
+        // En un gris el tono y la saturación son cero.
+        assert_eq!(rgb_to_hsv(Vec3::repeat(0.4)), Vec3::new(0.0, 0.0, 0.4));
+    }
+
+    #[test]
+    fn shadow_map_only_dims_the_first_light() {
+        // Dos luces con la misma dirección: el mapa de sombras se calculó para la primera y no debe oscurecer
+        // a la segunda aunque sus direcciones sean iguales.
+        let light = DirectionalLight { dir: Vec3::y(), color: Vec3::repeat(1.0), intensity: 1.0 };
+        let lights = [light, DirectionalLight { intensity: 0.5, ..light }];
+        let ctx = FragmentContext {
+            world_position: Vec3::zeros(),
+            local_position: Vec3::zeros(),
+            body_position: Vec3::zeros(),
+            body_to_world: Mat3::identity(),
+            normal: Vec3::y(),
+            uv: Vec2::zeros(),
+            view_dir: Vec3::z(),
+            time: 0.0,
+            lights: &lights,
+            occluders: &[],
+            rings: &[],
+            shadow: Some(0.25),
+            textures: &[],
+            material: Material::default(),
+        };
+
+        assert_eq!(ctx.light_visibility(0), 0.25);
+        assert_eq!(ctx.light_visibility(1), 1.0);
+    }
+}
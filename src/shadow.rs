@@ -0,0 +1,152 @@
+// Mapa de sombras de una luz direccional: la profundidad de la escena vista desde la luz, con la que se decide
+// qué fragmentos quedan tapados por otra geometría.
+use crate::mesh::ObjMesh;
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+// Radio del filtro PCF en texeles: se promedian (2 * radio + 1)² comparaciones para suavizar el borde.
+const PCF_RADIUS: i32 = 2;
+
+// Sesgos en texeles del mapa. El desplazamiento sobre la normal y el de profundidad evitan que una superficie se
+// sombree a sí misma por la diferencia entre sus facetas y las muestras del mapa ("acné"). El de profundidad
+// crece con la pendiente de la superficie vista desde la luz, hasta `MAX_SLOPE`.
+const NORMAL_OFFSET: f32 = 1.5;
+const DEPTH_BIAS: f32 = 1.5;
+const MAX_SLOPE: f32 = 10.0;
+
+// Profundidad desde una luz direccional con proyección ortográfica. El mapa cubre una esfera alrededor de la
+// escena, así que todos los objetos que pueden proyectar sombra caben en él sin importar la dirección de la luz.
+pub struct ShadowMap {
+    pub resolution: usize, // Ancho y alto del mapa en texeles.
+    depth: Vec<f32>,       // Distancia de cada texel a la luz; infinito donde no hay geometría.
+    center: Vec3,          // Centro de la esfera que cubre el mapa.
+    radius: f32,           // Radio de esa esfera.
+    right: Vec3,           // Eje horizontal del mapa en el mundo.
+    up: Vec3,              // Eje vertical del mapa en el mundo.
+    forward: Vec3,         // Dirección en que viaja la luz; la profundidad crece en este sentido.
+}
+
+impl ShadowMap {
+    // Crea un mapa vacío de `resolution`×`resolution` texeles. Hay que llamar a `begin` antes de dibujar en él.
+    pub fn new(resolution: usize) -> Self {
+        let resolution = resolution.max(1);
+        ShadowMap {
+            resolution,
+            depth: vec![f32::INFINITY; resolution * resolution],
+            center: Vec3::zeros(),
+            radius: 1.0,
+            right: Vec3::x(),
+            up: Vec3::y(),
+            forward: -Vec3::z(),
+        }
+    }
+
+    // Prepara el mapa para la luz que llega por `light_dir` (dirección hacia la luz) y una escena contenida en la
+    // esfera de `radius` alrededor de `center`: orienta la proyección y borra las profundidades anteriores.
+    pub fn begin(&mut self, light_dir: &Vec3, center: Vec3, radius: f32) {
+        self.forward = -light_dir.normalize();
+        // Cualquier vector que no sea paralelo a la luz sirve para completar la base.
+        let helper = if self.forward.y.abs() < 0.99 { Vec3::y() } else { Vec3::x() };
+        self.right = self.forward.cross(&helper).normalize();
+        self.up = self.right.cross(&self.forward);
+        self.center = center;
+        self.radius = radius.max(1e-3);
+        self.depth.fill(f32::INFINITY);
+    }
+
+    // Tamaño de un texel en unidades del mundo.
+    fn texel_size(&self) -> f32 {
+        2.0 * self.radius / self.resolution as f32
+    }
+
+    // Lleva un punto del mundo al mapa: columna y fila en texeles (con decimales) y profundidad.
+    fn project(&self, point: &Vec3) -> (f32, f32, f32) {
+        let offset = point - self.center;
+        let scale = self.resolution as f32 * 0.5;
+        let x = (offset.dot(&self.right) / self.radius + 1.0) * scale;
+        let y = (1.0 - offset.dot(&self.up) / self.radius) * scale;
+        (x, y, offset.dot(&self.forward))
+    }
+
+    // Dibuja la profundidad de los triángulos de `mesh` transformados por `model_matrix`. Se dibujan las dos
+    // caras: desde la luz importa lo que tapa, no hacia dónde mira.
+    pub fn render_mesh(&mut self, mesh: &ObjMesh, model_matrix: &Mat4) {
+        let projected: Vec<_> = mesh
+            .vertices
+            .iter()
+            .map(|v| {
+                let world = model_matrix * Vec4::new(v.position.x, v.position.y, v.position.z, 1.0);
+                self.project(&world.xyz())
+            })
+            .collect();
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [i0, i1, i2] = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+            if let (Some(&a), Some(&b), Some(&c)) = (projected.get(i0), projected.get(i1), projected.get(i2)) {
+                self.rasterize(a, b, c);
+            }
+        }
+    }
+
+    // Rellena un triángulo ya proyectado conservando la profundidad más cercana a la luz en cada texel. Con
+    // proyección ortográfica la profundidad se interpola linealmente, sin corrección de perspectiva.
+    fn rasterize(&mut self, a: (f32, f32, f32), b: (f32, f32, f32), c: (f32, f32, f32)) {
+        let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+        if area.abs() < 1e-8 {
+            return;
+        }
+
+        let size = self.resolution as f32;
+        let min_x = a.0.min(b.0).min(c.0).floor().max(0.0) as usize;
+        let min_y = a.1.min(b.1).min(c.1).floor().max(0.0) as usize;
+        let max_x = a.0.max(b.0).max(c.0).ceil().min(size) as usize;
+        let max_y = a.1.max(b.1).max(c.1).ceil().min(size) as usize;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // Pesos baricéntricos en el centro del texel; el signo del área hace que sirvan ambos órdenes.
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = ((b.0 - px) * (c.1 - py) - (b.1 - py) * (c.0 - px)) / area;
+                let w1 = ((c.0 - px) * (a.1 - py) - (c.1 - py) * (a.0 - px)) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let depth = w0 * a.2 + w1 * b.2 + w2 * c.2;
+                let stored = &mut self.depth[y * self.resolution + x];
+                if depth < *stored {
+                    *stored = depth;
+                }
+            }
+        }
+    }
+
+    // Devuelve qué fracción de la luz llega al punto `world_pos` de una superficie con normal `normal`: 1.0 si
+    // nada lo tapa y 0.0 si está completamente en sombra. Promedia varias comparaciones vecinas (PCF) para que
+    // el borde de la sombra sea suave. Los puntos fuera del mapa se consideran iluminados.
+    pub fn visibility(&self, world_pos: &Vec3, normal: &Vec3) -> f32 {
+        let texel = self.texel_size();
+        let (x, y, depth) = self.project(&(world_pos + normal * (texel * NORMAL_OFFSET)));
+
+        // En una superficie inclinada respecto a la luz la profundidad cambia de un texel al siguiente, y las
+        // muestras vecinas del filtro están hasta `PCF_RADIUS` texeles más lejos; el sesgo cubre esa diferencia.
+        let cos = (-normal.dot(&self.forward)).max(1e-3);
+        let slope = ((1.0 - cos * cos).max(0.0).sqrt() / cos).min(MAX_SLOPE);
+        let depth = depth - texel * (DEPTH_BIAS + slope * (PCF_RADIUS as f32 + 1.0));
+        let (x, y) = (x.floor() as i32, y.floor() as i32);
+        let size = self.resolution as i32;
+
+        let mut lit = 0;
+        let mut total = 0;
+        for dy in -PCF_RADIUS..=PCF_RADIUS {
+            for dx in -PCF_RADIUS..=PCF_RADIUS {
+                let (sx, sy) = (x + dx, y + dy);
+                total += 1;
+                if sx < 0 || sy < 0 || sx >= size || sy >= size || depth <= self.depth[(sy * size + sx) as usize] {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f32 / total as f32
+    }
+}
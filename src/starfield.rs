@@ -0,0 +1,77 @@
+// Importa el búfer de fotogramas y el tipo Color para dibujar el fondo de estrellas.
+use crate::framebuffer::{Color, Framebuffer};
+
+// Define un fondo de estrellas determinista. Cada píxel decide si tiene una estrella a partir de un hash
+// de sus coordenadas y la semilla, así que el patrón es estable de un fotograma a otro sin guardar nada.
+#[derive(Debug, Clone, Copy)]
+pub struct Starfield {
+    pub density: f32,  // Probabilidad de que un píxel tenga una estrella (por ejemplo 0.002).
+    pub seed: u32,     // Semilla del hash; cambiarla produce otro cielo.
+    pub twinkle: bool, // Si es verdadero, el brillo de cada estrella oscila ligeramente con el tiempo.
+}
+
+impl Starfield {
+    // Crea un fondo de estrellas con la densidad y semilla dadas, con centelleo activado.
+    pub fn new(density: f32, seed: u32) -> Self {
+        Starfield {
+            density,
+            seed,
+            twinkle: true,
+        }
+    }
+
+    // Dibuja las estrellas en los píxeles donde todavía no se ha dibujado geometría (profundidad infinita).
+    // No modifica el búfer de profundidad, así que puede llamarse antes o después de dibujar los planetas.
+    pub fn draw(&self, framebuffer: &mut Framebuffer, time: f32) {
+        // Umbral sobre el hash de 32 bits equivalente a la densidad pedida.
+        let threshold = (self.density.clamp(0.0, 1.0) as f64 * u32::MAX as f64) as u32;
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let h = hash(x as u32, y as u32, self.seed);
+                if h >= threshold {
+                    continue;
+                }
+
+                // Hashes adicionales dan a cada estrella su brillo, tono y fase de centelleo.
+                let brightness = 0.4 + 0.6 * unit(hash(x as u32, y as u32, self.seed ^ 0x9E37_79B9));
+                let tint = unit(hash(x as u32, y as u32, self.seed ^ 0x85EB_CA6B));
+                let twinkle = if self.twinkle {
+                    let phase = unit(hash(x as u32, y as u32, self.seed ^ 0xC2B2_AE35)) * std::f32::consts::TAU;
+                    0.85 + 0.15 * (time * 2.0 + phase).sin()
+                } else {
+                    1.0
+                };
+
+                // La mayoría de las estrellas son blancas; algunas tienen un tono azulado.
+                let intensity = brightness * twinkle;
+                let blue_shift = if tint < 0.3 { 0.25 } else { 0.0 };
+                let color = Color::new(
+                    ((1.0 - blue_shift) * intensity * 255.0) as u8,
+                    ((1.0 - blue_shift * 0.5) * intensity * 255.0) as u8,
+                    (intensity * 255.0) as u8,
+                );
+
+                framebuffer.set_background_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+// Hash entero de tres componentes con buena dispersión de bits.
+#[inline]
+fn hash(x: u32, y: u32, seed: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x8DA6_B343) ^ y.wrapping_mul(0xD816_3841) ^ seed.wrapping_mul(0xCB1A_B31F);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    h
+}
+
+// Convierte un hash en un valor entre 0.0 y 1.0.
+#[inline]
+fn unit(h: u32) -> f32 {
+    h as f32 / u32::MAX as f32
+}
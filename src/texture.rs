@@ -0,0 +1,178 @@
+// Importa el tipo Color para guardar los texeles y Vec2 para las coordenadas de textura.
+use crate::framebuffer::Color;
+use nalgebra_glm::Vec2;
+
+// Define cómo se combinan los texeles al muestrear una textura.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleMode {
+    Nearest,  // Toma el texel más cercano; se ve pixelado al ampliar.
+    Bilinear, // Mezcla los cuatro texeles más cercanos según la posición dentro de ellos.
+}
+
+// Define qué pasa con las coordenadas que quedan fuera de [0, 1] en un eje.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressMode {
+    Wrap,  // La textura se repite.
+    Clamp, // Se usa el texel del borde.
+}
+
+// Define una textura en memoria: una imagen RGB guardada fila por fila, empezando por la fila superior.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    pub width: usize,              // Ancho en texeles.
+    pub height: usize,             // Alto en texeles.
+    pub data: Vec<Color>,          // Texeles en orden de filas; `data[y * width + x]`.
+    pub sample_mode: SampleMode,   // Filtrado al muestrear.
+    pub address: [AddressMode; 2], // Direccionamiento en U y en V.
+}
+
+impl Texture {
+    // Crea una textura a partir de sus texeles. Devuelve un error si la cantidad no coincide con el tamaño.
+    pub fn from_colors(width: usize, height: usize, data: Vec<Color>) -> Result<Self, String> {
+        if width == 0 || height == 0 || data.len() != width * height {
+            return Err(format!(
+                "Texture data has {} texels, expected {}x{}",
+                data.len(),
+                width,
+                height
+            ));
+        }
+
+        // Los mapas de planetas se repiten alrededor de la longitud (U) pero no de un polo al otro (V).
+        Ok(Texture {
+            width,
+            height,
+            data,
+            sample_mode: SampleMode::Bilinear,
+            address: [AddressMode::Wrap, AddressMode::Clamp],
+        })
+    }
+
+    // Crea una textura de un solo texel de `color`; se muestrea igual en todas las coordenadas.
+    pub fn solid(color: Color) -> Self {
+        Texture {
+            width: 1,
+            height: 1,
+            data: vec![color],
+            sample_mode: SampleMode::Nearest,
+            address: [AddressMode::Clamp, AddressMode::Clamp],
+        }
+    }
+
+    // Carga una textura desde un archivo de imagen. El canal alfa, si existe, se descarta.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("Error loading texture '{}': {}", path, e))?
+            .to_rgb8();
+
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let data = image.pixels().map(|p| Color::new(p[0], p[1], p[2])).collect();
+
+        Texture::from_colors(width, height, data).map_err(|e| format!("Error loading texture '{}': {}", path, e))
+    }
+
+    // Muestrea la textura en las coordenadas `uv` según `sample_mode` y `address`. U crece hacia la derecha
+    // y V hacia abajo, como en las mallas procedurales (V = 0 es la fila superior).
+    pub fn sample(&self, uv: Vec2) -> Color {
+        match self.sample_mode {
+            SampleMode::Nearest => self.sample_nearest(uv),
+            SampleMode::Bilinear => self.sample_bilinear(uv),
+        }
+    }
+
+    // Devuelve el texel que contiene las coordenadas `uv`.
+    fn sample_nearest(&self, uv: Vec2) -> Color {
+        let x = address(uv.x * self.width as f32, self.width, self.address[0]);
+        let y = address(uv.y * self.height as f32, self.height, self.address[1]);
+        self.texel(x, y)
+    }
+
+    // Mezcla los cuatro texeles cuyos centros rodean las coordenadas `uv`. Los centros están en
+    // (i + 0.5) / tamaño, así que en el centro exacto de un texel se obtiene su color sin mezclar.
+    fn sample_bilinear(&self, uv: Vec2) -> Color {
+        let fx = uv.x * self.width as f32 - 0.5;
+        let fy = uv.y * self.height as f32 - 0.5;
+        let (tx, ty) = (fx - fx.floor(), fy - fy.floor());
+
+        let x0 = address(fx, self.width, self.address[0]);
+        let x1 = address(fx + 1.0, self.width, self.address[0]);
+        let y0 = address(fy, self.height, self.address[1]);
+        let y1 = address(fy + 1.0, self.height, self.address[1]);
+
+        let top = self.texel(x0, y0).to_vec3().lerp(&self.texel(x1, y0).to_vec3(), tx);
+        let bottom = self.texel(x0, y1).to_vec3().lerp(&self.texel(x1, y1).to_vec3(), tx);
+        let color = top.lerp(&bottom, ty) * 255.0;
+
+        Color::new(color.x.round() as u8, color.y.round() as u8, color.z.round() as u8)
+    }
+
+    #[inline]
+    fn texel(&self, x: usize, y: usize) -> Color {
+        self.data[y * self.width + x]
+    }
+}
+
+// Convierte una coordenada en texeles (sin normalizar) en un índice dentro de [0, size) según el direccionamiento.
+#[inline]
+fn address(coord: f32, size: usize, mode: AddressMode) -> usize {
+    let index = coord.floor() as i64;
+    match mode {
+        AddressMode::Wrap => index.rem_euclid(size as i64) as usize,
+        AddressMode::Clamp => index.clamp(0, size as i64 - 1) as usize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Textura de 2×2 con un color distinto en cada texel: rojo y verde arriba, azul y blanco abajo.
+    fn quad() -> Texture {
+        let colors = vec![
+            Color::new(255, 0, 0),
+            Color::new(0, 255, 0),
+            Color::new(0, 0, 255),
+            Color::new(255, 255, 255),
+        ];
+        Texture::from_colors(2, 2, colors).expect("4 texels fit a 2x2 texture")
+    }
+
+    #[test]
+    fn corners_sample_the_corner_texels() {
+        let mut texture = quad();
+        texture.address = [AddressMode::Clamp, AddressMode::Clamp];
+        let corners = [(0.0, 0.0, 0), (1.0, 0.0, 1), (0.0, 1.0, 2), (1.0, 1.0, 3)];
+
+        // Con las dos direcciones limitadas al borde, cada esquina es su texel sin mezclar en ambos modos.
+        for mode in [SampleMode::Nearest, SampleMode::Bilinear] {
+            texture.sample_mode = mode;
+            for (u, v, texel) in corners {
+                assert_eq!(texture.sample(Vec2::new(u, v)), texture.data[texel], "{:?} en ({}, {})", mode, u, v);
+            }
+        }
+
+        // Con la repetición en U de `from_colors`, el borde izquierdo y el derecho mezclan ambas columnas por igual.
+        let texture = quad();
+        assert_eq!(texture.sample(Vec2::new(0.0, 0.0)), Color::new(128, 128, 0));
+        assert_eq!(texture.sample(Vec2::new(1.0, 0.0)), Color::new(128, 128, 0));
+        assert_eq!(texture.sample(Vec2::new(1.0, 1.0)), Color::new(128, 128, 255));
+    }
+
+    #[test]
+    fn midpoint_between_two_texels_averages_only_when_bilinear() {
+        let mut texture = Texture::from_colors(2, 1, vec![Color::BLACK, Color::WHITE]).expect("2 texels fit 2x1");
+        texture.address = [AddressMode::Clamp, AddressMode::Clamp];
+
+        // u = 0.5 queda entre los centros de los dos texeles (0.25 y 0.75): 127.5 se redondea a 128.
+        let midpoint = Vec2::new(0.5, 0.5);
+        assert_eq!(texture.sample(midpoint), Color::new(128, 128, 128));
+        texture.sample_mode = SampleMode::Nearest;
+        assert_eq!(texture.sample(midpoint), Color::WHITE);
+        assert_eq!(texture.sample(Vec2::new(0.49, 0.5)), Color::BLACK);
+
+        // Fuera de [0, 1] el direccionamiento de cada eje decide el texel.
+        assert_eq!(texture.sample(Vec2::new(1.25, 0.5)), Color::WHITE);
+        texture.address[0] = AddressMode::Wrap;
+        assert_eq!(texture.sample(Vec2::new(1.25, 0.5)), Color::BLACK);
+    }
+}